@@ -53,6 +53,24 @@ where
     })
 }
 
+/// Returns `true` if the caller of the executed contract is the origin of
+/// the call stack.
+///
+/// This is the case if the contract has been called by a plain account
+/// directly and not by another contract.
+///
+/// # Note
+///
+/// This relies on an unstable interface of the contracts pallet.
+pub fn caller_is_origin<T>() -> bool
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        TypedEnvBackend::caller_is_origin::<T>(instance)
+    })
+}
+
 /// Returns the transferred value for the contract execution.
 ///
 /// # Errors
@@ -292,6 +292,14 @@ pub trait TypedEnvBackend: EnvBackend {
     /// For more details visit: [`caller`][`crate::caller`]
     fn caller<T: Environment>(&mut self) -> T::AccountId;
 
+    /// Returns `true` if the caller of the executed contract is the origin
+    /// of the call stack, i.e. a plain account and not another contract.
+    ///
+    /// # Note
+    ///
+    /// For more details visit: [`caller_is_origin`][`crate::caller_is_origin`]
+    fn caller_is_origin<T: Environment>(&mut self) -> bool;
+
     /// Returns the transferred value for the contract execution.
     ///
     /// # Note
@@ -324,6 +324,12 @@ impl TypedEnvBackend for EnvInstance {
             })
     }
 
+    fn caller_is_origin<T: Environment>(&mut self) -> bool {
+        // The experimental engine does not support contract-to-contract calls,
+        // so the caller is always the origin of the call stack.
+        true
+    }
+
     fn transferred_value<T: Environment>(&mut self) -> T::Balance {
         self.get_property::<T::Balance>(Engine::value_transferred)
             .unwrap_or_else(|error| {
@@ -15,10 +15,15 @@
 //! Operations on the off-chain testing environment.
 
 use super::{
+    impls::TopicsBuilder,
     EnvInstance,
     OnInstance,
 };
 use crate::{
+    topics::{
+        PrefixedValue,
+        TopicsBuilderBackend,
+    },
     Environment,
     Result,
 };
@@ -198,12 +203,20 @@ where
     })
 }
 
-/// Runs the given closure test function with the default configuration
-/// for the off-chain environment.
-pub fn run_test<T, F>(f: F) -> Result<()>
+/// Resets the whole off-chain environment to its default state.
+///
+/// This clears the emitted events, debug messages, contract storage and
+/// balances, resets the caller and callee to the default account and funds
+/// the default accounts just like [`run_test`] does.
+///
+/// # Note
+///
+/// `#[ink::test]` and [`run_test`] call this automatically before every test.
+/// Since the off-chain environment is thread local it is only required to call
+/// this explicitly in order to start over within a single test.
+pub fn reset<T>() -> Result<()>
 where
     T: Environment,
-    F: FnOnce(DefaultAccounts<T>) -> Result<()>,
     <T as Environment>::AccountId: From<[u8; 32]>,
 {
     let default_accounts = default_accounts::<T>();
@@ -234,7 +247,19 @@ where
             .engine
             .set_balance(scale::Encode::encode(&default_accounts.frank), 0);
     });
-    f(default_accounts)
+    Ok(())
+}
+
+/// Runs the given closure test function with the default configuration
+/// for the off-chain environment.
+pub fn run_test<T, F>(f: F) -> Result<()>
+where
+    T: Environment,
+    F: FnOnce(DefaultAccounts<T>) -> Result<()>,
+    <T as Environment>::AccountId: From<[u8; 32]>,
+{
+    reset::<T>()?;
+    f(default_accounts::<T>())
 }
 
 /// Returns the default accounts for testing purposes:
@@ -284,6 +309,60 @@ pub fn recorded_events() -> impl Iterator<Item = EmittedEvent> {
     })
 }
 
+/// Returns the number of emitted events recorded since the last reset.
+pub fn emitted_event_count() -> usize {
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        instance.engine.get_emitted_events().count()
+    })
+}
+
+/// Returns the raw topics of the recorded event emitted at `index`.
+///
+/// # Panics
+///
+/// If no event has been recorded at `index`.
+pub fn event_topics<T>(index: usize) -> Vec<Vec<u8>>
+where
+    T: Environment,
+{
+    recorded_events()
+        .nth(index)
+        .unwrap_or_else(|| panic!("no event recorded at index {}", index))
+        .topics
+}
+
+/// Returns the topic recorded for an `#[ink(topic)]` event field holding `value`.
+///
+/// The `prefix` identifies the field, e.g. `b"Erc20::Transfer::from"`, and is
+/// hashed together with the value. Since such a topic cannot be decoded back into
+/// its value, compare it against the topics returned by [`event_topics`] instead.
+pub fn event_topic<T, V>(prefix: &[u8], value: &V) -> Vec<u8>
+where
+    T: Environment,
+    V: scale::Encode,
+{
+    let mut builder = TopicsBuilder::default();
+    TopicsBuilderBackend::<T>::push_topic(&mut builder, &PrefixedValue { prefix, value });
+    builder.topics.remove(0)
+}
+
+/// Asserts that exactly `expected` events have been emitted since the last reset.
+///
+/// # Panics
+///
+/// If the number of recorded events differs from `expected`.
+pub fn assert_event_count<T>(expected: usize)
+where
+    T: Environment,
+{
+    let count = emitted_event_count();
+    assert_eq!(
+        count, expected,
+        "expected {} emitted events but found {}",
+        expected, count
+    );
+}
+
 /// Tests if a contract terminates successfully after `self.env().terminate()`
 /// has been called.
 ///
@@ -41,3 +41,11 @@ fn topics_builder() -> Result<()> {
         Ok(())
     })
 }
+
+#[test]
+fn caller_is_origin_holds() -> Result<()> {
+    crate::test::run_test::<crate::DefaultEnvironment, _>(|_| {
+        assert!(crate::caller_is_origin::<crate::DefaultEnvironment>());
+        Ok(())
+    })
+}
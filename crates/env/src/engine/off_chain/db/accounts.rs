@@ -240,11 +240,22 @@ impl Account {
             .and_then(|contract| contract.storage.get_storage::<T>(at))
     }
 
+    /// Returns `true` if the contract storage holds a value at the key.
+    pub fn contains_storage(&self, at: Key) -> Result<bool> {
+        self.contract_or_err()
+            .map(|contract| contract.storage.contains_storage(at))
+    }
+
     /// Returns the total number of reads and write from and to the contract's storage.
     pub fn get_storage_rw(&self) -> Result<(usize, usize)> {
         self.contract_or_err().map(|contract| contract.get_rw())
     }
 
+    /// Returns the total number of clears of the contract's storage.
+    pub fn get_storage_clears(&self) -> Result<usize> {
+        self.contract_or_err().map(|contract| contract.storage.get_clears())
+    }
+
     /// Returns the amount of used storage entries.
     pub fn count_used_storage_cells(&self) -> Result<usize> {
         self.contract_or_err()
@@ -295,7 +306,13 @@ pub struct ContractStorage {
     /// The total number of reads to the storage.
     count_reads: Cell<usize>,
     /// The total number of writes to the storage.
+    ///
+    /// # Note
+    ///
+    /// Clearing a storage cell counts as a write as well.
     count_writes: usize,
+    /// The total number of clears of the storage.
+    count_clears: usize,
 }
 
 impl ContractStorage {
@@ -305,6 +322,7 @@ impl ContractStorage {
             entries: BTreeMap::new(),
             count_reads: Cell::new(0),
             count_writes: 0,
+            count_clears: 0,
         }
     }
 
@@ -313,6 +331,11 @@ impl ContractStorage {
         (self.count_reads.get(), self.count_writes)
     }
 
+    /// Returns the number of clears of the contract storage.
+    pub fn get_clears(&self) -> usize {
+        self.count_clears
+    }
+
     /// Returns the decoded storage at the key if any.
     pub fn get_storage<T>(&self, at: Key) -> Result<Option<T>>
     where
@@ -335,9 +358,19 @@ impl ContractStorage {
         self.entries.insert(at, new_value.encode());
     }
 
+    /// Returns `true` if there is a storage entry at the given key.
+    ///
+    /// # Note
+    ///
+    /// This does not count as a read from the contract storage.
+    pub fn contains_storage(&self, at: Key) -> bool {
+        self.entries.contains_key(&at)
+    }
+
     /// Removes the value from storage entries at the given key.
     pub fn clear_storage(&mut self, at: Key) {
         self.count_writes += 1;
+        self.count_clears += 1;
         self.entries.remove(&at);
     }
 
@@ -117,6 +117,18 @@ impl ChainSpec {
         self.block_time.decode().map_err(Into::into)
     }
 
+    /// Set the targeted block time for the chain.
+    ///
+    /// # Note
+    ///
+    /// This is the interval by which advancing the chain bumps the block timestamp.
+    pub fn set_block_time<T>(&mut self, block_time: T::Timestamp)
+    where
+        T: Environment,
+    {
+        self.block_time = OffTimestamp::new(&block_time)
+    }
+
     /// The balance a contract needs to deposit per storage byte to stay alive indefinitely.
     pub fn deposit_per_storage_byte<T>(&self) -> Result<T::Balance>
     where
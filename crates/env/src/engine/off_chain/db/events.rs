@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::super::OffHash;
+use super::super::{
+    OffBlockNumber,
+    OffHash,
+};
 use crate::{
     hash::{
         Blake2x256,
@@ -95,7 +98,8 @@ impl EmittedEvent {
 
 /// Records all emitted events for later inspection.
 pub struct EmittedEventsRecorder {
-    emitted_events: Vec<EmittedEvent>,
+    /// The emitted events together with the number of the block they were emitted in.
+    emitted_events: Vec<(OffBlockNumber, EmittedEvent)>,
 }
 
 impl EmittedEventsRecorder {
@@ -111,18 +115,30 @@ impl EmittedEventsRecorder {
         self.emitted_events.clear();
     }
 
-    /// Records a new emitted event.
-    pub fn record<T, E>(&mut self, new_event: E)
+    /// Records a new event emitted in the block with the given number.
+    pub fn record<T, E>(&mut self, block_number: T::BlockNumber, new_event: E)
     where
         T: Environment,
         E: Topics + scale::Encode,
     {
-        self.emitted_events
-            .push(EmittedEvent::new::<T, E>(new_event));
+        self.emitted_events.push((
+            OffBlockNumber::new(&block_number),
+            EmittedEvent::new::<T, E>(new_event),
+        ));
     }
 
     /// Returns an iterator over the emitted events in their emission order.
-    pub fn emitted_events(&self) -> core::slice::Iter<EmittedEvent> {
-        self.emitted_events.iter()
+    pub fn emitted_events(&self) -> impl Iterator<Item = &EmittedEvent> {
+        self.emitted_events.iter().map(|(_, event)| event)
+    }
+
+    /// Returns an iterator over the emitted events in their emission order
+    /// together with the number of the block they were emitted in.
+    pub fn emitted_events_with_block_number(
+        &self,
+    ) -> impl Iterator<Item = (&OffBlockNumber, &EmittedEvent)> {
+        self.emitted_events
+            .iter()
+            .map(|(block_number, event)| (block_number, event))
     }
 }
@@ -46,6 +46,10 @@ pub struct ExecContext {
     pub call_data: CallData,
     /// The output of the contract execution.
     pub output: Option<Bytes>,
+    /// Whether the contract execution has been reverted.
+    pub reverted: bool,
+    /// The gas consumed on the storage of the callee, see `test::gas_breakdown`.
+    pub gas_consumed: u64,
 }
 
 impl ExecContext {
@@ -219,6 +223,8 @@ where
             gas,
             call_data: self.call_data.unwrap(),
             output: None,
+            reverted: false,
+            gas_consumed: 0,
         }
     }
 }
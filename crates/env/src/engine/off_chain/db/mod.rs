@@ -37,6 +37,7 @@ pub use self::{
     events::{
         EmittedEvent,
         EmittedEventsRecorder,
+        TopicsBuilder,
     },
     exec_context::ExecContext,
 };
@@ -16,6 +16,7 @@ use super::{
     hashing,
     Account,
     EnvInstance,
+    TypedEncoded,
 };
 use crate::{
     call::{
@@ -125,7 +126,23 @@ impl EnvBackend for EnvInstance {
     where
         V: scale::Encode,
     {
-        self.callee_account_mut()
+        let storage_deposit_limit = self.storage_deposit_limit;
+        let callee = self.callee_account_mut();
+        if let Some(limit) = storage_deposit_limit {
+            let used_cells = callee
+                .count_used_storage_cells()
+                .expect("callee account is not a smart contract");
+            let is_new_cell = !callee
+                .contains_storage(*key)
+                .expect("callee account is not a smart contract");
+            if is_new_cell && used_cells >= limit {
+                panic!(
+                    "storage deposit limit exceeded: cannot occupy more than {} cells",
+                    limit
+                )
+            }
+        }
+        callee
             .set_storage(*key, value)
             .expect("callee account is not a smart contract");
     }
@@ -290,6 +307,11 @@ impl EnvInstance {
             .get_account_mut::<T>(destination)
             .expect("the account must exist already or has just been created")
             .set_balance::<T>(dst_value + value)?;
+        self.transfers.push((
+            TypedEncoded::new(&src_id),
+            TypedEncoded::new(destination),
+            TypedEncoded::new(&value),
+        ));
         Ok(())
     }
 
@@ -332,6 +354,10 @@ impl TypedEnvBackend for EnvInstance {
             })
     }
 
+    fn caller_is_origin<T: Environment>(&mut self) -> bool {
+        !self.caller_is_contract
+    }
+
     fn transferred_value<T: Environment>(&mut self) -> T::Balance {
         self.exec_context()
             .expect(UNINITIALIZED_EXEC_CONTEXT)
@@ -405,7 +431,13 @@ impl TypedEnvBackend for EnvInstance {
         T: Environment,
         Event: Topics + scale::Encode,
     {
-        self.emitted_events.record::<T, Event>(new_event)
+        let block_number = self
+            .current_block()
+            .expect(UNINITIALIZED_EXEC_CONTEXT)
+            .number::<T>()
+            .expect("could not read the current block number");
+        self.emitted_events
+            .record::<T, Event>(block_number, new_event)
     }
 
     fn invoke_contract<T, Args>(&mut self, params: &CallParams<T, Args, ()>) -> Result<()>
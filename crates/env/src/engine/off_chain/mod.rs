@@ -68,6 +68,8 @@ pub enum OffChainError {
     UninitializedExecutionContext,
     #[from(ignore)]
     UnregisteredChainExtension,
+    #[from(ignore)]
+    NoRecordedCall,
 }
 
 pub type Result<T> = core::result::Result<T, OffChainError>;
@@ -92,6 +94,24 @@ pub struct EnvInstance {
     emitted_events: EmittedEventsRecorder,
     /// Set to true to disable clearing storage
     clear_storage_disabled: bool,
+    /// Advances the chain by a single block if auto-advance is enabled.
+    ///
+    /// # Note
+    ///
+    /// This is stored as a function pointer since advancing the chain
+    /// requires knowledge about the environmental types which are only
+    /// known upon enabling auto-advance.
+    auto_advance: Option<fn(&mut EnvInstance) -> crate::Result<()>>,
+    /// The execution context of the most recent top-level contract execution.
+    last_call: Option<ExecContext>,
+    /// If `true` the caller of contract executions is treated as another
+    /// contract instead of the origin of the call stack.
+    caller_is_contract: bool,
+    /// The maximum number of storage cells a contract may occupy if limited.
+    storage_deposit_limit: Option<usize>,
+    /// The balance transfers in the order they have been performed as
+    /// `(from, to, value)`.
+    transfers: Vec<(OffAccountId, OffAccountId, OffBalance)>,
 }
 
 impl EnvInstance {
@@ -106,6 +126,11 @@ impl EnvInstance {
             chain_extension_handler: ChainExtensionHandler::new(),
             emitted_events: EmittedEventsRecorder::new(),
             clear_storage_disabled: false,
+            auto_advance: None,
+            last_call: None,
+            caller_is_contract: false,
+            storage_deposit_limit: None,
+            transfers: Vec::new(),
         }
     }
 
@@ -137,6 +162,11 @@ impl EnvInstance {
         self.chain_extension_handler.reset();
         self.emitted_events.reset();
         self.clear_storage_disabled = false;
+        self.auto_advance = None;
+        self.last_call = None;
+        self.caller_is_contract = false;
+        self.storage_deposit_limit = None;
+        self.transfers.clear();
     }
 
     /// Initializes the whole off-chain environment.
@@ -213,18 +243,44 @@ impl EnvInstance {
     }
 
     /// Advances the chain by a single block.
+    ///
+    /// # Note
+    ///
+    /// The number of the new block follows the number of the current block.
+    /// Its timestamp is strictly greater than the timestamp of the current block,
+    /// even if the block time of the chain is zero.
     pub fn advance_block<T>(&mut self) -> crate::Result<()>
     where
         T: Environment,
     {
-        let new_block_number = T::BlockNumber::from(self.blocks.len() as u32);
-        let new_timestamp = self.current_block()?.timestamp::<T>()?
-            + self.chain_spec.block_time::<T>()?;
+        let new_block_number =
+            self.current_block()?.number::<T>()? + T::BlockNumber::from(1u32);
+        let block_time = core::cmp::max(
+            self.chain_spec.block_time::<T>()?,
+            T::Timestamp::from(1u32),
+        );
+        let new_timestamp = self.current_block()?.timestamp::<T>()? + block_time;
         self.blocks
             .push(Block::new::<T>(new_block_number, new_timestamp));
         Ok(())
     }
 
+    /// Pops the top execution context.
+    ///
+    /// If the popped context belonged to a top-level contract execution it is
+    /// recorded as the last call and, if auto-advance is enabled, the chain is
+    /// advanced by a single block.
+    pub fn pop_exec_context(&mut self) -> crate::Result<()> {
+        let popped = self.exec_context.pop();
+        if self.exec_context.len() <= 1 {
+            self.last_call = popped;
+            if let Some(advance) = self.auto_advance {
+                advance(self)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the current execution context.
     fn exec_context(&self) -> Result<&ExecContext> {
         self.exec_context
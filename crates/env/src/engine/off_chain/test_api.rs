@@ -21,12 +21,25 @@ pub use super::{
     EmittedEvent,
 };
 use super::{
-    db::ExecContext,
+    db::{
+        ExecContext,
+        TopicsBuilder,
+    },
     AccountError,
     EnvInstance,
+    OffChainError,
     OnInstance,
 };
 use crate::{
+    hash::{
+        CryptoHash,
+        HashOutput,
+    },
+    topics::{
+        PrefixedValue,
+        TopicsBuilderBackend,
+    },
+    EnvBackend,
     Environment,
     Result,
 };
@@ -69,9 +82,92 @@ pub fn push_execution_context<T>(
 ///
 /// Together with [`push_execution_context`] this can be used to emulated
 /// nested calls.
+///
+/// If auto-advance has been enabled via [`set_auto_advance`] the chain is
+/// advanced by a single block after popping a top-level execution context.
 pub fn pop_execution_context() {
     <EnvInstance as OnInstance>::on_instance(|instance| {
-        instance.exec_context.pop();
+        instance
+            .pop_exec_context()
+            .unwrap_or_else(|error| panic!("could not advance the block: {:?}", error))
+    })
+}
+
+/// Emulates a top-level contract execution of `f`.
+///
+/// Returns the result of `f` or the payload of its panic.
+///
+/// # Note
+///
+/// This wraps `f` with [`push_execution_context`] and [`pop_execution_context`].
+/// If `f` panics the execution is recorded as reverted, see [`last_call_info`].
+/// The gas consumed by `f` is measured with [`gas_breakdown`].
+pub fn execute_message<T, F, R>(
+    caller: T::AccountId,
+    callee: T::AccountId,
+    gas_limit: u64,
+    endowment: T::Balance,
+    call_data: CallData,
+    f: F,
+) -> std::thread::Result<R>
+where
+    T: Environment,
+    F: FnOnce() -> R + UnwindSafe,
+{
+    push_execution_context::<T>(caller, callee, gas_limit, endowment, call_data);
+    let before = gas_breakdown::<T>().unwrap_or_default();
+    let result = std::panic::catch_unwind(f);
+    let after = gas_breakdown::<T>().unwrap_or_default();
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        if let Ok(exec_context) = instance.exec_context_mut() {
+            exec_context.gas_consumed = after.total().saturating_sub(before.total());
+            exec_context.reverted = result.is_err();
+        }
+    });
+    pop_execution_context();
+    result
+}
+
+/// Summary of a top-level contract execution.
+pub struct CallInfo<T>
+where
+    T: Environment,
+{
+    /// The value transferred to the callee.
+    pub transferred: T::Balance,
+    /// The gas consumed by the execution.
+    ///
+    /// # Note
+    ///
+    /// This is the [`total`][`GasBreakdown::total`] that [`gas_breakdown`]
+    /// reports for the storage operations of the callee during the execution,
+    /// hence it is measured in the same synthetic units.
+    /// Executions that are not run via [`execute_message`] always report `0`.
+    pub gas_consumed: u64,
+    /// Whether the execution has been reverted.
+    pub reverted: bool,
+}
+
+/// Returns a summary of the most recent top-level contract execution.
+///
+/// # Errors
+///
+/// - If no top-level contract execution has been recorded yet.
+/// - If the underlying `transferred` type does not match.
+pub fn last_call_info<T>() -> Result<CallInfo<T>>
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        let last_call = instance
+            .last_call
+            .as_ref()
+            .ok_or(OffChainError::NoRecordedCall)?;
+        Ok(CallInfo {
+            transferred: last_call.transferred_value::<T>()?,
+            gas_consumed: last_call.gas_consumed,
+            reverted: last_call.reverted,
+        })
     })
 }
 
@@ -157,6 +253,61 @@ where
     .map_err(Into::into)
 }
 
+/// Sets the timestamp of the current block.
+///
+/// # Note
+///
+/// The timestamp is returned by [`block_timestamp`][`crate::block_timestamp`]
+/// until it is set again or [`advance_block`] moves it forward by the block time.
+///
+/// # Panics
+///
+/// If `timestamp` lies before the timestamp of the current block since time never
+/// goes backward on a real chain. Use [`set_block_timestamp_unchecked`] to
+/// deliberately move the clock backward.
+pub fn set_block_timestamp<T>(timestamp: T::Timestamp) -> Result<()>
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        let block = instance.current_block_mut()?;
+        assert!(
+            timestamp >= block.timestamp::<T>()?,
+            "the block timestamp must not move backward"
+        );
+        block.set_timestamp::<T>(timestamp)
+    })
+    .map_err(Into::into)
+}
+
+/// Sets the timestamp of the current block without checking that time does not
+/// move backward.
+pub fn set_block_timestamp_unchecked<T>(timestamp: T::Timestamp) -> Result<()>
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        instance.current_block_mut()?.set_timestamp::<T>(timestamp)
+    })
+    .map_err(Into::into)
+}
+
+/// Sets the number of the current block.
+///
+/// # Note
+///
+/// The number is returned by [`block_number`][`crate::block_number`] until it is
+/// set again or [`advance_block`] moves on to the next block.
+pub fn set_block_number<T>(block_number: T::BlockNumber) -> Result<()>
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        instance.current_block_mut()?.set_number::<T>(block_number)
+    })
+    .map_err(Into::into)
+}
+
 /// Update the [`ChainSpec`](`crate::test::ChainSpec`) for the test environment
 pub fn update_chain_spec<F>(f: F) -> Result<()>
 where
@@ -183,6 +334,61 @@ pub fn recorded_debug_messages() -> impl Iterator<Item = String> {
     })
 }
 
+/// Returns the hash of `input` as computed by the off-chain environment.
+///
+/// # Note
+///
+/// Allows to compare the hashing of the off-chain environment, e.g. used to
+/// derive storage keys, against known test vectors of the on-chain runtime.
+pub fn hash_with<T, H>(input: &[u8]) -> <H as HashOutput>::Type
+where
+    T: Environment,
+    H: CryptoHash,
+{
+    let mut output = <H as HashOutput>::Type::default();
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        EnvBackend::hash_bytes::<H>(instance, input, &mut output)
+    });
+    output
+}
+
+/// A balance transfer recorded as `(from, to, value)`.
+pub type RecordedTransfer<T> = (
+    <T as Environment>::AccountId,
+    <T as Environment>::AccountId,
+    <T as Environment>::Balance,
+);
+
+/// Returns the balance transfers performed by contracts in order.
+///
+/// # Note
+///
+/// This records transfers via [`crate::transfer`] as well as the transfer of
+/// the remaining balance upon contract termination. Changes to balances via
+/// [`set_account_balance`] are not recorded.
+///
+/// # Errors
+///
+/// - If the underlying account or balance types do not match.
+pub fn recorded_transfers<T>() -> Result<Vec<RecordedTransfer<T>>>
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        instance
+            .transfers
+            .iter()
+            .map(|(from, to, value)| {
+                Ok((
+                    from.decode::<T::AccountId>()?,
+                    to.decode::<T::AccountId>()?,
+                    value.decode::<T::Balance>()?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()
+    })
+}
+
 /// Returns the recorded emitted events in order.
 pub fn recorded_events() -> impl Iterator<Item = EmittedEvent> {
     <EnvInstance as OnInstance>::on_instance(|instance| {
@@ -200,7 +406,125 @@ pub fn recorded_events() -> impl Iterator<Item = EmittedEvent> {
     })
 }
 
+/// Returns the recorded emitted events in order together with the number
+/// of the block they were emitted in.
+///
+/// # Errors
+///
+/// - If the underlying block number type does not match.
+pub fn recorded_events_with_block_number<T>(
+) -> Result<impl Iterator<Item = (T::BlockNumber, EmittedEvent)>>
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        instance
+            .emitted_events
+            .emitted_events_with_block_number()
+            .map(|(block_number, event)| {
+                let block_number = block_number.decode::<T::BlockNumber>()?;
+                Ok((block_number, event.clone()))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(IntoIterator::into_iter)
+    })
+}
+
+/// Returns the recorded events emitted in the block with the given number in order.
+///
+/// # Note
+///
+/// Together with [`advance_block`] this partitions the emitted events by block.
+///
+/// # Errors
+///
+/// - If the underlying block number type does not match.
+pub fn recorded_events_in_block<T>(
+    block_number: T::BlockNumber,
+) -> Result<impl Iterator<Item = EmittedEvent>>
+where
+    T: Environment,
+{
+    let events = recorded_events_with_block_number::<T>()?
+        .filter(|(emitted_in, _)| *emitted_in == block_number)
+        .map(|(_, event)| event)
+        .collect::<Vec<_>>();
+    Ok(events.into_iter())
+}
+
+/// Returns the number of emitted events recorded since the last reset.
+pub fn emitted_event_count() -> usize {
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        instance.emitted_events.emitted_events().count()
+    })
+}
+
+/// Returns the raw topics of the recorded event emitted at `index`.
+///
+/// # Panics
+///
+/// If no event has been recorded at `index`.
+pub fn event_topics<T>(index: usize) -> Vec<Vec<u8>>
+where
+    T: Environment,
+{
+    let event = recorded_events()
+        .nth(index)
+        .unwrap_or_else(|| panic!("no event recorded at index {}", index));
+    event
+        .topics
+        .iter()
+        .map(|topic| {
+            topic
+                .encoded_bytes()
+                .expect("recorded topics must be initialized")
+                .to_vec()
+        })
+        .collect()
+}
+
+/// Returns the topic recorded for an `#[ink(topic)]` event field holding `value`.
+///
+/// The `prefix` identifies the field, e.g. `b"Erc20::Transfer::from"`, and is
+/// hashed together with the value. Since such a topic cannot be decoded back into
+/// its value, compare it against the topics returned by [`event_topics`] instead.
+pub fn event_topic<T, V>(prefix: &[u8], value: &V) -> Vec<u8>
+where
+    T: Environment,
+    V: scale::Encode,
+{
+    let mut builder = TopicsBuilder::default();
+    TopicsBuilderBackend::<T>::push_topic(&mut builder, &PrefixedValue { prefix, value });
+    TopicsBuilderBackend::<T>::output(builder)[0]
+        .encoded_bytes()
+        .expect("pushed topics must be initialized")
+        .to_vec()
+}
+
+/// Asserts that exactly `expected` events have been emitted since the last reset.
+///
+/// # Panics
+///
+/// If the number of recorded events differs from `expected`.
+pub fn assert_event_count<T>(expected: usize)
+where
+    T: Environment,
+{
+    let count = emitted_event_count();
+    assert_eq!(
+        count, expected,
+        "expected {} emitted events but found {}",
+        expected, count
+    );
+}
+
 /// Advances the chain by a single block.
+///
+/// # Note
+///
+/// The block number is incremented by one and the block timestamp is bumped by
+/// the block time of the [`ChainSpec`], which can be changed via
+/// [`update_chain_spec`] and [`ChainSpec::set_block_time`].
 pub fn advance_block<T>() -> Result<()>
 where
     T: Environment,
@@ -208,6 +532,63 @@ where
     <EnvInstance as OnInstance>::on_instance(|instance| instance.advance_block::<T>())
 }
 
+/// Enables or disables automatically advancing the chain after every
+/// top-level contract execution.
+///
+/// # Note
+///
+/// A contract execution is emulated by a pair of [`push_execution_context`]
+/// and [`pop_execution_context`] calls. With auto-advance enabled every top-level
+/// execution bumps the block number by one and the block timestamp by the
+/// block time of the [`ChainSpec`].
+pub fn set_auto_advance<T>(enabled: bool)
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        instance.auto_advance = if enabled {
+            Some(EnvInstance::advance_block::<T>)
+        } else {
+            None
+        };
+    })
+}
+
+/// Sets whether the caller of contract executions is another contract.
+///
+/// # Note
+///
+/// This controls the result of [`caller_is_origin`][`crate::caller_is_origin`]
+/// which returns `false` while the flag is set. The flag is cleared whenever
+/// the off-chain environment is reset.
+pub fn set_caller_is_contract<T>(is_contract: bool)
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        instance.caller_is_contract = is_contract
+    })
+}
+
+/// Limits the number of storage cells a contract may occupy to `cells`.
+///
+/// # Note
+///
+/// Writing to a new storage cell beyond the limit panics which emulates the
+/// revert of a contract execution that cannot pay for its storage deposit.
+/// Use [`execute_message`] in order to observe the revert. Storage writes
+/// that precede the failing one are not rolled back.
+///
+/// The limit is lifted whenever the off-chain environment is reset.
+pub fn set_storage_deposit_limit<T>(cells: usize)
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        instance.storage_deposit_limit = Some(cells)
+    })
+}
+
 /// Set to true to disable clearing storage
 ///
 /// # Note
@@ -272,6 +653,25 @@ where
     })
 }
 
+/// Resets the whole off-chain environment to its default state.
+///
+/// This clears the emitted events, debug messages, contract storage, balances,
+/// execution contexts and blocks and then sets up the same defaults that
+/// [`run_test`] provides.
+///
+/// # Note
+///
+/// `#[ink::test]` and [`run_test`] call this automatically before every test.
+/// Since the off-chain environment is thread local it is only required to call
+/// this explicitly in order to start over within a single test.
+pub fn reset<T>() -> Result<()>
+where
+    T: Environment,
+    <T as Environment>::AccountId: From<[u8; 32]>,
+{
+    initialize_or_reset_as_default::<T>()
+}
+
 /// Runs the given closure test function with the default configuration
 /// for the off-chain environment.
 pub fn run_test<T, F>(f: F) -> Result<()>
@@ -280,7 +680,7 @@ where
     F: FnOnce(DefaultAccounts<T>) -> Result<()>,
     <T as Environment>::AccountId: From<[u8; 32]>,
 {
-    initialize_or_reset_as_default::<T>()?;
+    reset::<T>()?;
     let default_accounts = default_accounts::<T>()?;
     f(default_accounts)
 }
@@ -300,6 +700,93 @@ where
     })
 }
 
+/// The units charged per read of a contract storage cell by [`gas_breakdown`].
+pub const STORAGE_READ_UNITS: u64 = 1;
+
+/// The units charged per write of a contract storage cell by [`gas_breakdown`].
+pub const STORAGE_WRITE_UNITS: u64 = 5;
+
+/// The units charged per clear of a contract storage cell by [`gas_breakdown`].
+pub const STORAGE_CLEAR_UNITS: u64 = 2;
+
+/// The gas spent by a contract split up by the kind of operation.
+///
+/// # Note
+///
+/// All amounts are synthetic units that only weigh storage operations against
+/// each other, they do not correspond to the weights charged by a real chain.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct GasBreakdown {
+    /// The gas spent on reading contract storage cells.
+    pub reads: u64,
+    /// The gas spent on writing contract storage cells.
+    pub writes: u64,
+    /// The gas spent on clearing contract storage cells.
+    pub clears: u64,
+    /// The gas spent on computation.
+    ///
+    /// # Note
+    ///
+    /// The off-chain environment does not meter computation, hence this is always `0`.
+    pub compute: u64,
+}
+
+impl GasBreakdown {
+    /// Returns the total gas of the breakdown.
+    pub fn total(&self) -> u64 {
+        self.reads + self.writes + self.clears + self.compute
+    }
+}
+
+impl core::ops::Sub for GasBreakdown {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            reads: self.reads - rhs.reads,
+            writes: self.writes - rhs.writes,
+            clears: self.clears - rhs.clears,
+            compute: self.compute - rhs.compute,
+        }
+    }
+}
+
+/// Returns the gas spent on the storage of the currently executing contract.
+///
+/// # Note
+///
+/// Every storage operation is charged a fixed number of [`STORAGE_READ_UNITS`],
+/// [`STORAGE_WRITE_UNITS`] or [`STORAGE_CLEAR_UNITS`]. The breakdown accumulates
+/// all operations since the off-chain environment has been reset, so in order
+/// to measure a single message subtract the breakdown taken before it.
+///
+/// # Errors
+///
+/// - If the off-chain environment has not been initialized.
+/// - If the currently executing account is not a contract.
+pub fn gas_breakdown<T>() -> Result<GasBreakdown>
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        let callee = instance.exec_context()?.callee.decode::<T::AccountId>()?;
+        let account = instance
+            .accounts
+            .get_account::<T>(&callee)
+            .ok_or_else(|| AccountError::no_account_for_id::<T>(&callee))?;
+        let (reads, writes) = account.get_storage_rw()?;
+        let clears = account.get_storage_clears()?;
+        // Clears are counted as writes by the contract storage.
+        let writes = writes - clears;
+        Ok(GasBreakdown {
+            reads: reads as u64 * STORAGE_READ_UNITS,
+            writes: writes as u64 * STORAGE_WRITE_UNITS,
+            clears: clears as u64 * STORAGE_CLEAR_UNITS,
+            compute: 0,
+        })
+    })
+}
+
 /// Returns the amount of storage cells used by the account `account_id`.
 ///
 /// Returns `None` if the `account_id` is non-existent.
@@ -317,6 +804,32 @@ where
     })
 }
 
+/// Returns `true` if the currently executing contract occupies no storage cells.
+///
+/// # Note
+///
+/// Terminating a contract removes its account together with all of its
+/// storage, hence this also returns `true` after a contract termination.
+/// In order to validate that a contract cleans up its storage before it
+/// terminates check this right before calling [`crate::terminate_contract`].
+///
+/// # Errors
+///
+/// - If the off-chain environment has not been initialized.
+/// - If the currently executing account is not a contract.
+pub fn contract_storage_is_empty<T>() -> Result<bool>
+where
+    T: Environment,
+{
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        let callee = instance.exec_context()?.callee.decode::<T::AccountId>()?;
+        match instance.accounts.get_account::<T>(&callee) {
+            Some(account) => Ok(account.count_used_storage_cells()? == 0),
+            None => Ok(true),
+        }
+    })
+}
+
 /// Returns the account id of the currently executing contract.
 pub fn get_current_contract_account_id<T>() -> Result<T::AccountId>
 where
@@ -329,6 +842,62 @@ where
     })
 }
 
+/// Runs `f` against the storage of the contract `account_id` and returns its result.
+///
+/// # Note
+///
+/// The off-chain environment keeps the contract storage separately for every
+/// contract account. Within `f` the given account is the callee of the
+/// execution so that all storage operations apply to its own storage only.
+/// This allows to test multiple instances of the same contract side by side.
+///
+/// A contract account without balance is created if `account_id` does not
+/// exist, yet. The current contract calls into `account_id` and its execution
+/// context is restored once `f` returns or panics. This is not recorded as a
+/// contract execution, see [`last_call_info`].
+pub fn with_contract<T, R>(account_id: T::AccountId, f: impl FnOnce() -> R) -> R
+where
+    T: Environment,
+{
+    use crate::call::Selector;
+
+    /// Pops the execution context of the callee when dropped.
+    struct PopExecContext;
+
+    impl Drop for PopExecContext {
+        fn drop(&mut self) {
+            <EnvInstance as OnInstance>::on_instance(|instance| {
+                instance.exec_context.pop();
+            });
+        }
+    }
+
+    <EnvInstance as OnInstance>::on_instance(|instance| {
+        if instance.accounts.get_account::<T>(&account_id).is_none() {
+            instance
+                .accounts
+                .add_contract_account::<T>(account_id.clone(), T::Balance::from(0u32));
+        }
+        let caller = instance
+            .exec_context()
+            .ok()
+            .and_then(|exec_context| exec_context.callee.decode::<T::AccountId>().ok())
+            .unwrap_or_else(|| account_id.clone());
+        instance.exec_context.push(
+            ExecContext::build::<T>()
+                .caller(caller)
+                .callee(account_id)
+                .gas(0u64)
+                .transferred_value(T::Balance::from(0u32))
+                .call_data(CallData::new(Selector::new([0x00; 4])))
+                .finish(),
+        )
+    });
+    // Restores the execution context of the caller even if `f` panics.
+    let _pop_exec_context = PopExecContext;
+    f()
+}
+
 /// The result of a successful contract termination.
 #[derive(scale::Encode, scale::Decode)]
 pub struct ContractTerminationResult<E>
@@ -377,3 +946,44 @@ pub fn assert_contract_termination<T, F>(
     assert_eq!(res.beneficiary, expected_beneficiary);
     assert_eq!(res.transferred, expected_balance);
 }
+
+/// Asserts that `f` panics with a message containing `expected`.
+///
+/// # Usage
+///
+/// ```no_compile
+/// ink_env::test::assert_panics("key must be valid", || {
+///     corrupted_map.take(&key);
+/// });
+/// ```
+///
+/// # Note
+///
+/// In contrast to [`std::panic::catch_unwind`] `f` is not required to be
+/// unwind safe in order to test panics of storage types holding lazily loaded
+/// state. Everything that `f` has mutated might be in an inconsistent state
+/// afterwards.
+///
+/// # Panics
+///
+/// - If `f` does not panic.
+/// - If the panic message does not contain `expected`.
+/// - If the panic payload is not a message, e.g. after a contract termination.
+pub fn assert_panics<F>(expected: &str, f: F)
+where
+    F: FnOnce(),
+{
+    let payload = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(f))
+        .expect_err("the closure did not panic as expected");
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .expect("panic payload is not a message");
+    assert!(
+        message.contains(expected),
+        "panic message {:?} does not contain {:?}",
+        message,
+        expected,
+    );
+}
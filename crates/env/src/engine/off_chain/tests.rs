@@ -94,3 +94,725 @@ fn gas_price() -> crate::Result<()> {
         Ok(())
     })
 }
+
+/// Emulates a top-level contract execution from `caller` to the default contract.
+fn call_contract(caller: crate::AccountId) {
+    use crate::{
+        call::Selector,
+        test::CallData,
+        DefaultEnvironment,
+    };
+    let callee = crate::test::get_current_contract_account_id::<DefaultEnvironment>()
+        .expect("contract account id must exist");
+    crate::test::push_execution_context::<DefaultEnvironment>(
+        caller,
+        callee,
+        1_000_000,
+        0,
+        CallData::new(Selector::new([0x00; 4])),
+    );
+    crate::test::pop_execution_context();
+}
+
+#[test]
+fn last_call_info_works() -> Result<()> {
+    use crate::{
+        call::Selector,
+        test::CallData,
+        DefaultEnvironment,
+    };
+    crate::test::run_test::<DefaultEnvironment, _>(|accounts| {
+        assert!(crate::test::last_call_info::<DefaultEnvironment>().is_err());
+        let callee =
+            crate::test::get_current_contract_account_id::<DefaultEnvironment>()?;
+        let call_data = || CallData::new(Selector::new([0x00; 4]));
+
+        // A payable message receiving some value.
+        let received = crate::test::execute_message::<DefaultEnvironment, _, _>(
+            accounts.alice,
+            callee,
+            1_000_000,
+            42,
+            call_data(),
+            crate::transferred_value::<DefaultEnvironment>,
+        );
+        assert_eq!(received.ok(), Some(42));
+        let info = crate::test::last_call_info::<DefaultEnvironment>()?;
+        assert_eq!(info.transferred, 42);
+        assert_eq!(info.gas_consumed, 0);
+        assert!(!info.reverted);
+
+        // The gas consumed is priced like the gas breakdown of the callee.
+        let key = Key::from([0x42; 32]);
+        let result = crate::test::execute_message::<DefaultEnvironment, _, _>(
+            accounts.alice,
+            callee,
+            1_000_000,
+            0,
+            call_data(),
+            || {
+                let value = crate::get_contract_storage::<u32>(&key).unwrap();
+                crate::set_contract_storage(&key, &value.unwrap_or(0));
+            },
+        );
+        assert!(result.is_ok());
+        let info = crate::test::last_call_info::<DefaultEnvironment>()?;
+        assert_eq!(
+            info.gas_consumed,
+            crate::test::STORAGE_READ_UNITS + crate::test::STORAGE_WRITE_UNITS
+        );
+
+        // A message that panics is recorded as reverted.
+        let result = crate::test::execute_message::<DefaultEnvironment, _, _>(
+            accounts.bob,
+            callee,
+            1_000_000,
+            0,
+            call_data(),
+            || panic!("revert"),
+        );
+        assert!(result.is_err());
+        let info = crate::test::last_call_info::<DefaultEnvironment>()?;
+        assert_eq!(info.transferred, 0);
+        assert!(info.reverted);
+        Ok(())
+    })
+}
+
+#[test]
+fn auto_advance_works() -> Result<()> {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|accounts| {
+        // Without auto-advance executions do not progress the chain.
+        call_contract(accounts.alice);
+        assert_eq!(crate::block_number::<DefaultEnvironment>(), 0);
+        assert_eq!(crate::block_timestamp::<DefaultEnvironment>(), 0);
+
+        // Every top-level execution advances the chain by a single block.
+        crate::test::set_auto_advance::<DefaultEnvironment>(true);
+        for _ in 0..3 {
+            call_contract(accounts.alice);
+        }
+        assert_eq!(crate::block_number::<DefaultEnvironment>(), 3);
+        assert_eq!(crate::block_timestamp::<DefaultEnvironment>(), 3 * 5);
+
+        crate::test::set_auto_advance::<DefaultEnvironment>(false);
+        call_contract(accounts.alice);
+        assert_eq!(crate::block_number::<DefaultEnvironment>(), 3);
+        Ok(())
+    })
+}
+
+#[test]
+fn block_timestamp_moves_forward() -> Result<()> {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|_| {
+        crate::test::set_block_timestamp::<DefaultEnvironment>(100)?;
+        assert_eq!(crate::block_timestamp::<DefaultEnvironment>(), 100);
+        // Setting the same timestamp again does not move time backward.
+        crate::test::set_block_timestamp::<DefaultEnvironment>(100)?;
+        for _ in 0..3 {
+            let before = crate::block_timestamp::<DefaultEnvironment>();
+            crate::test::advance_block::<DefaultEnvironment>()?;
+            assert!(crate::block_timestamp::<DefaultEnvironment>() > before);
+        }
+        assert_eq!(crate::block_timestamp::<DefaultEnvironment>(), 100 + 3 * 5);
+        Ok(())
+    })
+}
+
+#[test]
+#[should_panic(expected = "the block timestamp must not move backward")]
+fn set_block_timestamp_backward_panics() {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|_| {
+        crate::test::set_block_timestamp::<DefaultEnvironment>(100)?;
+        crate::test::set_block_timestamp::<DefaultEnvironment>(99)
+    })
+    .unwrap()
+}
+
+#[test]
+fn set_block_timestamp_unchecked_moves_backward() -> Result<()> {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|_| {
+        crate::test::set_block_timestamp::<DefaultEnvironment>(100)?;
+        crate::test::set_block_timestamp_unchecked::<DefaultEnvironment>(42)?;
+        assert_eq!(crate::block_timestamp::<DefaultEnvironment>(), 42);
+        Ok(())
+    })
+}
+
+/// Returns the block number as seen from within a contract message.
+fn block_number_in_message() -> u32 {
+    use crate::{
+        call::Selector,
+        test::CallData,
+        DefaultEnvironment,
+    };
+    let accounts = crate::test::default_accounts::<DefaultEnvironment>()
+        .expect("default accounts must exist");
+    let callee = crate::test::get_current_contract_account_id::<DefaultEnvironment>()
+        .expect("contract account id must exist");
+    crate::test::execute_message::<DefaultEnvironment, _, _>(
+        accounts.alice,
+        callee,
+        1_000_000,
+        0,
+        CallData::new(Selector::new([0x00; 4])),
+        crate::block_number::<DefaultEnvironment>,
+    )
+    .expect("the message must not panic")
+}
+
+#[test]
+fn advance_block_moves_number_and_timestamp() -> Result<()> {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|_| {
+        crate::test::update_chain_spec(|chain_spec| {
+            chain_spec.set_block_time::<DefaultEnvironment>(10)
+        })?;
+        for block in 1..=4 {
+            crate::test::advance_block::<DefaultEnvironment>()?;
+            assert_eq!(crate::block_number::<DefaultEnvironment>(), block);
+            assert_eq!(
+                crate::block_timestamp::<DefaultEnvironment>(),
+                u64::from(block) * 10
+            );
+        }
+        assert_eq!(block_number_in_message(), 4);
+        Ok(())
+    })
+}
+
+#[test]
+fn set_block_number_works() -> Result<()> {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|_| {
+        crate::test::set_block_number::<DefaultEnvironment>(100)?;
+        assert_eq!(crate::block_number::<DefaultEnvironment>(), 100);
+        assert_eq!(crate::block_timestamp::<DefaultEnvironment>(), 0);
+        assert_eq!(block_number_in_message(), 100);
+        crate::emit_event::<DefaultEnvironment, _>(Ping(0));
+
+        // Advancing the chain continues from the set block number.
+        crate::test::advance_block::<DefaultEnvironment>()?;
+        crate::test::advance_block::<DefaultEnvironment>()?;
+        assert_eq!(crate::block_number::<DefaultEnvironment>(), 102);
+        assert_eq!(crate::block_timestamp::<DefaultEnvironment>(), 2 * 5);
+        assert_eq!(block_number_in_message(), 102);
+        crate::emit_event::<DefaultEnvironment, _>(Ping(1));
+
+        let recorded =
+            crate::test::recorded_events_with_block_number::<DefaultEnvironment>()?
+                .map(|(block_number, event)| (block_number, event.data))
+                .collect::<Vec<_>>();
+        assert_eq!(recorded, vec![(100, vec![0]), (102, vec![1])]);
+        Ok(())
+    })
+}
+
+/// An event without topics for testing purposes.
+#[derive(scale::Encode)]
+struct Ping(u8);
+
+impl crate::Topics for Ping {
+    type RemainingTopics = crate::topics::state::NoRemainingTopics;
+
+    fn topics<E, B>(
+        &self,
+        builder: crate::topics::TopicsBuilder<crate::topics::state::Uninit, E, B>,
+    ) -> <B as crate::topics::TopicsBuilderBackend<E>>::Output
+    where
+        E: crate::Environment,
+        B: crate::topics::TopicsBuilderBackend<E>,
+    {
+        builder.build::<Self>().finish()
+    }
+}
+
+#[test]
+fn recorded_events_are_partitioned_by_block() -> Result<()> {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|_| {
+        crate::emit_event::<DefaultEnvironment, _>(Ping(0));
+        crate::test::advance_block::<DefaultEnvironment>()?;
+        crate::emit_event::<DefaultEnvironment, _>(Ping(1));
+        crate::emit_event::<DefaultEnvironment, _>(Ping(2));
+        crate::test::advance_block::<DefaultEnvironment>()?;
+        crate::test::advance_block::<DefaultEnvironment>()?;
+        crate::emit_event::<DefaultEnvironment, _>(Ping(3));
+
+        let recorded =
+            crate::test::recorded_events_with_block_number::<DefaultEnvironment>()?
+                .map(|(block_number, event)| (block_number, event.data))
+                .collect::<Vec<_>>();
+        assert_eq!(
+            recorded,
+            vec![(0, vec![0]), (1, vec![1]), (1, vec![2]), (3, vec![3])]
+        );
+        let in_block = |block_number| {
+            crate::test::recorded_events_in_block::<DefaultEnvironment>(block_number)
+                .map(|events| events.map(|event| event.data).collect::<Vec<_>>())
+        };
+        assert_eq!(in_block(0)?, vec![vec![0]]);
+        assert_eq!(in_block(1)?, vec![vec![1], vec![2]]);
+        assert_eq!(in_block(2)?, Vec::<Vec<u8>>::new());
+        assert_eq!(in_block(3)?, vec![vec![3]]);
+        // The plain recording is unaffected.
+        assert_eq!(crate::test::recorded_events().count(), 4);
+        Ok(())
+    })
+}
+
+/// An event with a single topic for testing purposes.
+#[derive(scale::Encode)]
+struct Pong {
+    sender: [u8; 32],
+}
+
+impl crate::Topics for Pong {
+    type RemainingTopics = [crate::topics::state::HasRemainingTopics; 1];
+
+    fn topics<E, B>(
+        &self,
+        builder: crate::topics::TopicsBuilder<crate::topics::state::Uninit, E, B>,
+    ) -> <B as crate::topics::TopicsBuilderBackend<E>>::Output
+    where
+        E: crate::Environment,
+        B: crate::topics::TopicsBuilderBackend<E>,
+    {
+        builder
+            .build::<Self>()
+            .push_topic::<crate::topics::PrefixedValue<[u8; 32]>>(
+                &crate::topics::PrefixedValue {
+                    prefix: b"Contract::Pong::sender",
+                    value: &self.sender,
+                },
+            )
+            .finish()
+    }
+}
+
+#[test]
+fn event_topics_match_event_topic() -> Result<()> {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|_| {
+        crate::emit_event::<DefaultEnvironment, _>(Ping(0));
+        crate::emit_event::<DefaultEnvironment, _>(Pong { sender: [0x01; 32] });
+
+        assert!(crate::test::event_topics::<DefaultEnvironment>(0).is_empty());
+        let topics = crate::test::event_topics::<DefaultEnvironment>(1);
+        assert_eq!(
+            topics,
+            vec![crate::test::event_topic::<DefaultEnvironment, _>(
+                b"Contract::Pong::sender",
+                &[0x01_u8; 32]
+            )]
+        );
+        assert_ne!(
+            topics[0],
+            crate::test::event_topic::<DefaultEnvironment, _>(
+                b"Contract::Pong::sender",
+                &[0x02_u8; 32]
+            )
+        );
+        // Short encodings are recorded as is instead of being hashed.
+        let mut expected = vec![0x00, 0x07];
+        expected.resize(32, 0x00);
+        assert_eq!(
+            crate::test::event_topic::<DefaultEnvironment, _>(b"", &0x07_u8),
+            expected
+        );
+        Ok(())
+    })
+}
+
+#[test]
+#[should_panic(expected = "no event recorded at index 0")]
+fn event_topics_panics_without_event() {
+    crate::test::run_test::<crate::DefaultEnvironment, _>(|_| {
+        crate::test::event_topics::<crate::DefaultEnvironment>(0);
+        Ok(())
+    })
+    .unwrap()
+}
+
+#[test]
+fn caller_is_origin_follows_configuration() -> Result<()> {
+    /// Emulates a message that only runs its hooks for contract callers.
+    fn run_hooks() -> bool {
+        !crate::caller_is_origin::<crate::DefaultEnvironment>()
+    }
+
+    crate::test::run_test::<crate::DefaultEnvironment, _>(|_| {
+        assert!(crate::caller_is_origin::<crate::DefaultEnvironment>());
+        assert!(!run_hooks());
+        crate::test::set_caller_is_contract::<crate::DefaultEnvironment>(true);
+        assert!(!crate::caller_is_origin::<crate::DefaultEnvironment>());
+        assert!(run_hooks());
+        crate::test::set_caller_is_contract::<crate::DefaultEnvironment>(false);
+        assert!(!run_hooks());
+        Ok(())
+    })
+}
+
+#[test]
+fn reset_restores_defaults() -> Result<()> {
+    use crate::{
+        call::Selector,
+        test::CallData,
+        DefaultEnvironment,
+    };
+    crate::test::run_test::<DefaultEnvironment, _>(|accounts| {
+        let key = Key::from([0x42; 32]);
+        let callee =
+            crate::test::get_current_contract_account_id::<DefaultEnvironment>()?;
+        let alice_balance =
+            crate::test::get_account_balance::<DefaultEnvironment>(accounts.alice)?;
+
+        crate::set_contract_storage(&key, &1u32);
+        crate::emit_event::<DefaultEnvironment, _>(Ping(0));
+        crate::debug_message("dirty");
+        crate::test::set_account_balance::<DefaultEnvironment>(accounts.alice, 1)?;
+        crate::test::advance_block::<DefaultEnvironment>()?;
+        crate::test::set_caller_is_contract::<DefaultEnvironment>(true);
+        crate::test::push_execution_context::<DefaultEnvironment>(
+            accounts.bob,
+            accounts.charlie,
+            0,
+            0,
+            CallData::new(Selector::new([0x00; 4])),
+        );
+
+        crate::test::reset::<DefaultEnvironment>()?;
+
+        assert_eq!(crate::get_contract_storage::<u32>(&key), Ok(None));
+        assert_eq!(crate::test::recorded_events().count(), 0);
+        assert_eq!(crate::test::recorded_debug_messages().count(), 0);
+        assert_eq!(
+            crate::test::get_account_balance::<DefaultEnvironment>(accounts.alice)?,
+            alice_balance
+        );
+        assert_eq!(crate::block_number::<DefaultEnvironment>(), 0);
+        assert_eq!(crate::caller::<DefaultEnvironment>(), accounts.alice);
+        assert_eq!(
+            crate::test::get_current_contract_account_id::<DefaultEnvironment>()?,
+            callee
+        );
+        assert!(crate::caller_is_origin::<DefaultEnvironment>());
+        Ok(())
+    })
+}
+
+#[test]
+fn storage_deposit_limit_reverts_execution() -> Result<()> {
+    use crate::{
+        call::Selector,
+        test::CallData,
+        DefaultEnvironment,
+    };
+    crate::test::run_test::<DefaultEnvironment, _>(|accounts| {
+        let callee =
+            crate::test::get_current_contract_account_id::<DefaultEnvironment>()?;
+        let insert_many = |count: u8| {
+            move || {
+                for n in 0..count {
+                    crate::set_contract_storage(&Key::from([n; 32]), &n);
+                }
+            }
+        };
+        let execute = |count| {
+            crate::test::execute_message::<DefaultEnvironment, _, _>(
+                accounts.alice,
+                callee,
+                1_000_000,
+                0,
+                CallData::new(Selector::new([0x00; 4])),
+                insert_many(count),
+            )
+        };
+        crate::test::set_storage_deposit_limit::<DefaultEnvironment>(3);
+
+        // Staying within the limit, overwriting occupied cells is always fine.
+        assert!(execute(3).is_ok());
+        assert!(execute(2).is_ok());
+        assert!(!crate::test::last_call_info::<DefaultEnvironment>()?.reverted);
+
+        // A loop of inserts hitting the limit reverts the execution.
+        assert!(execute(5).is_err());
+        assert!(crate::test::last_call_info::<DefaultEnvironment>()?.reverted);
+        assert_eq!(
+            crate::test::count_used_storage_cells::<DefaultEnvironment>(&callee)?,
+            3
+        );
+        Ok(())
+    })
+}
+
+#[test]
+fn recorded_transfers_works() -> Result<()> {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|accounts| {
+        let contract = crate::account_id::<DefaultEnvironment>();
+        crate::test::set_account_balance::<DefaultEnvironment>(contract, 100)?;
+        assert!(crate::test::recorded_transfers::<DefaultEnvironment>()?.is_empty());
+        crate::transfer::<DefaultEnvironment>(accounts.bob, 10)?;
+        crate::transfer::<DefaultEnvironment>(accounts.charlie, 5)?;
+        // Failed transfers are not recorded.
+        assert!(crate::transfer::<DefaultEnvironment>(accounts.bob, 1_000).is_err());
+        assert_eq!(
+            crate::test::recorded_transfers::<DefaultEnvironment>()?,
+            vec![(contract, accounts.bob, 10), (contract, accounts.charlie, 5)]
+        );
+        Ok(())
+    })
+}
+
+#[test]
+fn with_contract_isolates_storage() -> Result<()> {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|_| {
+        let token_a = crate::AccountId::from([0xA0; 32]);
+        let token_b = crate::AccountId::from([0xB0; 32]);
+        let balance_key = Key::from([0x42; 32]);
+        let set_balance = |token, balance: u128| {
+            crate::test::with_contract::<DefaultEnvironment, _>(token, || {
+                crate::set_contract_storage(&balance_key, &balance)
+            })
+        };
+        let get_balance = |token| {
+            crate::test::with_contract::<DefaultEnvironment, _>(token, || {
+                crate::get_contract_storage::<u128>(&balance_key)
+            })
+        };
+        set_balance(token_a, 100);
+        set_balance(token_b, 200);
+        assert_eq!(get_balance(token_a), Ok(Some(100)));
+        assert_eq!(get_balance(token_b), Ok(Some(200)));
+        set_balance(token_a, 50);
+        assert_eq!(get_balance(token_a), Ok(Some(50)));
+        assert_eq!(get_balance(token_b), Ok(Some(200)));
+
+        // The given contract is the callee only for the duration of the closure.
+        let contract =
+            crate::test::get_current_contract_account_id::<DefaultEnvironment>()?;
+        let callee = crate::test::with_contract::<DefaultEnvironment, _>(token_a, || {
+            crate::account_id::<DefaultEnvironment>()
+        });
+        assert_eq!(callee, token_a);
+        assert_eq!(crate::account_id::<DefaultEnvironment>(), contract);
+        assert_eq!(crate::get_contract_storage::<u128>(&balance_key), Ok(None));
+        assert_eq!(
+            crate::test::count_used_storage_cells::<DefaultEnvironment>(&token_a)?,
+            1
+        );
+        Ok(())
+    })
+}
+
+#[test]
+fn with_contract_restores_callee_after_panic() -> Result<()> {
+    use crate::DefaultEnvironment;
+    crate::test::run_test::<DefaultEnvironment, _>(|_| {
+        let token = crate::AccountId::from([0xA0; 32]);
+        let contract =
+            crate::test::get_current_contract_account_id::<DefaultEnvironment>()?;
+        crate::test::assert_panics("token failed", || {
+            crate::test::with_contract::<DefaultEnvironment, _>(token, || {
+                assert_eq!(crate::account_id::<DefaultEnvironment>(), token);
+                panic!("token failed")
+            })
+        });
+        assert_eq!(crate::account_id::<DefaultEnvironment>(), contract);
+        Ok(())
+    })
+}
+
+#[test]
+fn gas_breakdown_works() -> Result<()> {
+    use crate::{
+        test::{
+            GasBreakdown,
+            STORAGE_CLEAR_UNITS,
+            STORAGE_READ_UNITS,
+            STORAGE_WRITE_UNITS,
+        },
+        DefaultEnvironment,
+    };
+    /// Moves `value` from the balance stored at `from` to the one stored at `to`.
+    fn transfer(from: &Key, to: &Key, value: u128) {
+        let balance_from = crate::get_contract_storage::<u128>(from).unwrap().unwrap();
+        let balance_to = crate::get_contract_storage::<u128>(to).unwrap().unwrap_or(0);
+        crate::set_contract_storage(from, &(balance_from - value));
+        crate::set_contract_storage(to, &(balance_to + value));
+    }
+    crate::test::run_test::<DefaultEnvironment, _>(|_| {
+        let alice = Key::from([0x01; 32]);
+        let bob = Key::from([0x02; 32]);
+        crate::set_contract_storage(&alice, &100u128);
+        let before = crate::test::gas_breakdown::<DefaultEnvironment>()?;
+        assert_eq!(
+            before,
+            GasBreakdown {
+                writes: STORAGE_WRITE_UNITS,
+                ..Default::default()
+            }
+        );
+
+        // A transfer reads and writes both balances.
+        transfer(&alice, &bob, 30);
+        let spent = crate::test::gas_breakdown::<DefaultEnvironment>()? - before;
+        assert_eq!(
+            spent,
+            GasBreakdown {
+                reads: 2 * STORAGE_READ_UNITS,
+                writes: 2 * STORAGE_WRITE_UNITS,
+                clears: 0,
+                compute: 0,
+            }
+        );
+        assert_eq!(spent.total(), 2 * STORAGE_READ_UNITS + 2 * STORAGE_WRITE_UNITS);
+
+        // Clears are accounted separately from writes.
+        let before = crate::test::gas_breakdown::<DefaultEnvironment>()?;
+        crate::clear_contract_storage(&bob);
+        let spent = crate::test::gas_breakdown::<DefaultEnvironment>()? - before;
+        assert_eq!(spent.clears, STORAGE_CLEAR_UNITS);
+        assert_eq!(spent.writes, 0);
+        Ok(())
+    })
+}
+
+#[test]
+fn assert_panics_works() {
+    crate::test::assert_panics("must be valid", || panic!("key must be valid"));
+    crate::test::assert_panics("index 3", || panic!("out of bounds at index {}", 3));
+}
+
+#[test]
+#[should_panic(expected = "does not contain")]
+fn assert_panics_fails_for_other_messages() {
+    crate::test::assert_panics("must be valid", || panic!("storage entry was empty"));
+}
+
+#[test]
+fn hash_with_agrees_with_blake2_test_vectors() {
+    use crate::{
+        hash::Blake2x256,
+        DefaultEnvironment,
+    };
+    // Published BLAKE2b test vectors with an output length of 256 bits.
+    let test_vectors: &[(&[u8], [u8; 32])] = &[
+        (
+            b"",
+            [
+                14, 87, 81, 192, 38, 229, 67, 178, 232, 171, 46, 176, 96, 153, 218, 161,
+                209, 229, 223, 71, 119, 143, 119, 135, 250, 171, 69, 205, 241, 47, 227,
+                168,
+            ],
+        ),
+        (
+            b"abc",
+            [
+                189, 221, 129, 60, 99, 66, 57, 114, 49, 113, 239, 63, 238, 152, 87, 155,
+                148, 150, 78, 59, 177, 203, 62, 66, 114, 98, 200, 192, 104, 213, 35, 25,
+            ],
+        ),
+        (
+            b"The quick brown fox jumps over the lazy dog",
+            [
+                1, 113, 140, 236, 53, 205, 61, 121, 109, 208, 0, 32, 224, 191, 236, 180,
+                115, 173, 35, 69, 125, 6, 59, 117, 239, 242, 156, 15, 250, 46, 88, 169,
+            ],
+        ),
+    ];
+    for (input, expected) in test_vectors {
+        assert_eq!(
+            &crate::test::hash_with::<DefaultEnvironment, Blake2x256>(input),
+            expected
+        );
+    }
+}
+
+#[test]
+fn hash_with_agrees_with_keccak256_test_vectors() {
+    use crate::{
+        hash::Keccak256,
+        DefaultEnvironment,
+    };
+    // Published KECCAK-256 test vectors as used by Ethereum.
+    let test_vectors: &[(&[u8], [u8; 32])] = &[
+        (
+            b"",
+            [
+                0xC5, 0xD2, 0x46, 0x01, 0x86, 0xF7, 0x23, 0x3C, 0x92, 0x7E, 0x7D, 0xB2,
+                0xDC, 0xC7, 0x03, 0xC0, 0xE5, 0x00, 0xB6, 0x53, 0xCA, 0x82, 0x27, 0x3B,
+                0x7B, 0xFA, 0xD8, 0x04, 0x5D, 0x85, 0xA4, 0x70,
+            ],
+        ),
+        (
+            b"abc",
+            [
+                0x4E, 0x03, 0x65, 0x7A, 0xEA, 0x45, 0xA9, 0x4F, 0xC7, 0xD4, 0x7B, 0xA8,
+                0x26, 0xC8, 0xD6, 0x67, 0xC0, 0xD1, 0xE6, 0xE3, 0x3A, 0x64, 0xA0, 0x36,
+                0xEC, 0x44, 0xF5, 0x8F, 0xA1, 0x2D, 0x6C, 0x45,
+            ],
+        ),
+        (
+            b"The quick brown fox jumps over the lazy dog",
+            [
+                0x4D, 0x74, 0x1B, 0x6F, 0x1E, 0xB2, 0x9C, 0xB2, 0xA9, 0xB9, 0x91, 0x1C,
+                0x82, 0xF5, 0x6F, 0xA8, 0xD7, 0x3B, 0x04, 0x95, 0x9D, 0x3D, 0x9D, 0x22,
+                0x28, 0x95, 0xDF, 0x6C, 0x0B, 0x28, 0xAA, 0x15,
+            ],
+        ),
+    ];
+    for (input, expected) in test_vectors {
+        assert_eq!(
+            &crate::test::hash_with::<DefaultEnvironment, Keccak256>(input),
+            expected
+        );
+    }
+}
+
+#[test]
+fn hash_with_agrees_with_sha2x256_test_vectors() {
+    use crate::{
+        hash::Sha2x256,
+        DefaultEnvironment,
+    };
+    // Published SHA-256 test vectors.
+    let test_vectors: &[(&[u8], [u8; 32])] = &[
+        (
+            b"",
+            [
+                0xE3, 0xB0, 0xC4, 0x42, 0x98, 0xFC, 0x1C, 0x14, 0x9A, 0xFB, 0xF4, 0xC8,
+                0x99, 0x6F, 0xB9, 0x24, 0x27, 0xAE, 0x41, 0xE4, 0x64, 0x9B, 0x93, 0x4C,
+                0xA4, 0x95, 0x99, 0x1B, 0x78, 0x52, 0xB8, 0x55,
+            ],
+        ),
+        (
+            b"abc",
+            [
+                0xBA, 0x78, 0x16, 0xBF, 0x8F, 0x01, 0xCF, 0xEA, 0x41, 0x41, 0x40, 0xDE,
+                0x5D, 0xAE, 0x22, 0x23, 0xB0, 0x03, 0x61, 0xA3, 0x96, 0x17, 0x7A, 0x9C,
+                0xB4, 0x10, 0xFF, 0x61, 0xF2, 0x00, 0x15, 0xAD,
+            ],
+        ),
+        (
+            b"The quick brown fox jumps over the lazy dog",
+            [
+                0xD7, 0xA8, 0xFB, 0xB3, 0x07, 0xD7, 0x80, 0x94, 0x69, 0xCA, 0x9A, 0xBC,
+                0xB0, 0x08, 0x2E, 0x4F, 0x8D, 0x56, 0x51, 0xE4, 0x6D, 0x3C, 0xDB, 0x76,
+                0x2D, 0x02, 0xD0, 0xBF, 0x37, 0xC9, 0xE5, 0x92,
+            ],
+        ),
+    ];
+    for (input, expected) in test_vectors {
+        assert_eq!(
+            &crate::test::hash_with::<DefaultEnvironment, Sha2x256>(input),
+            expected
+        );
+    }
+}
@@ -323,6 +323,8 @@ mod sys {
 
     #[link(wasm_import_module = "__unstable__")]
     extern "C" {
+        pub fn seal_caller_is_origin() -> ReturnCode;
+
         pub fn seal_ecdsa_recover(
             // 65 bytes of ecdsa signature
             signature_ptr: Ptr32<[u8]>,
@@ -524,6 +526,11 @@ impl_seal_wrapper_for! {
     (minimum_balance => seal_minimum_balance),
 }
 
+pub fn caller_is_origin() -> bool {
+    let ret_val = unsafe { sys::seal_caller_is_origin() };
+    ret_val.into_u32() != 0
+}
+
 pub fn weight_to_fee(gas: u64, output: &mut &mut [u8]) {
     let mut output_len = output.len() as u32;
     {
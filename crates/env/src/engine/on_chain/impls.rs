@@ -357,6 +357,10 @@ impl TypedEnvBackend for EnvInstance {
         self.get_property_inplace::<T::AccountId>(ext::caller)
     }
 
+    fn caller_is_origin<T: Environment>(&mut self) -> bool {
+        ext::caller_is_origin()
+    }
+
     fn transferred_value<T: Environment>(&mut self) -> T::Balance {
         self.get_property_little_endian::<T::Balance>(ext::value_transferred)
     }
@@ -363,7 +363,7 @@ impl CallBuilder<'_> {
         let input_types = generator::input_types(message.inputs());
         let arg_list = generator::generate_argument_list(input_types.iter().cloned());
         let mut_tok = callable.receiver().is_ref_mut().then(|| quote! { mut });
-        let output = message.output();
+        let output = message.encoded_output();
         let output_sig = output.map_or_else(
             || quote! { () },
             |output| quote! { ::ink_env::call::utils::ReturnType<#output> },
@@ -313,12 +313,29 @@ impl Dispatch<'_> {
                 let selector_id = message.composed_selector().into_be_u32().hex_padded_suffixed();
                 let selector_bytes = message.composed_selector().hex_lits();
                 let output_tuple_type = message
-                    .output()
+                    .encoded_output()
                     .map(quote::ToTokens::to_token_stream)
                     .unwrap_or_else(|| quote! { () });
                 let input_bindings = generator::input_bindings(message.inputs());
                 let input_tuple_type = generator::input_types_tuple(message.inputs());
                 let input_tuple_bindings = generator::input_bindings_tuple(message.inputs());
+                let message_call = quote_spanned!(message_span=>
+                    #storage_ident::#message_ident( storage #( , #input_bindings )* )
+                );
+                // Messages returning a reference are dispatched without this callable
+                // so that their referent is encoded in place. For reflection purposes
+                // the callable yields an owned copy of the referent by decoding its
+                // encoding, which does not require the referent to be `Clone`.
+                let message_call = if message.returns_ref() {
+                    quote_spanned!(message_span=>
+                        <#output_tuple_type as ::scale::Decode>::decode(
+                            &mut &::scale::Encode::encode(#message_call)[..]
+                        )
+                        .expect("an encoded referent must decode as its owned type")
+                    )
+                } else {
+                    message_call
+                };
                 quote_spanned!(message_span=>
                     impl ::ink_lang::reflect::DispatchableMessageInfo<#selector_id> for #storage_ident {
                         type Input = #input_tuple_type;
@@ -327,7 +344,7 @@ impl Dispatch<'_> {
 
                         const CALLABLE: fn(&mut Self::Storage, Self::Input) -> Self::Output =
                             |storage, #input_tuple_bindings| {
-                                #storage_ident::#message_ident( storage #( , #input_bindings )* )
+                                #message_call
                             };
                         const SELECTOR: [::core::primitive::u8; 4usize] = [ #( #selector_bytes ),* ];
                         const PAYABLE: ::core::primitive::bool = #payable;
@@ -713,9 +730,22 @@ impl Dispatch<'_> {
             }
         };
 
+        // The messages are ordered like their dispatch IDs: inherent messages
+        // first, then trait messages.
+        let (inherent_impls, trait_impls): (Vec<_>, Vec<_>) = self
+            .contract
+            .module()
+            .impls()
+            .partition(|item_impl| item_impl.trait_path().is_none());
+        let messages = inherent_impls
+            .into_iter()
+            .chain(trait_impls)
+            .flat_map(|item_impl| item_impl.iter_messages())
+            .collect::<Vec<_>>();
         let message_execute = (0..count_messages).map(|index| {
             let message_span = message_spans[index];
             let message_ident = message_variant_ident(index);
+            let message = messages[index].callable();
             let message_callable = quote_spanned!(message_span=>
                 <#storage_ident as ::ink_lang::reflect::DispatchableMessageInfo<{
                     <#storage_ident as ::ink_lang::reflect::ContractDispatchableMessages<{
@@ -749,6 +779,24 @@ impl Dispatch<'_> {
                 .contract
                 .config()
                 .is_dynamic_storage_allocator_enabled();
+            // Messages returning a reference are called directly so that the
+            // referent can be encoded without cloning it first.
+            let (mut_token, message_result) = if message.returns_ref() {
+                let message_fn = message.ident();
+                let input_bindings = generator::input_bindings(message.inputs());
+                let input_tuple_bindings =
+                    generator::input_bindings_tuple(message.inputs());
+                let message_result = quote_spanned!(message_span=> {
+                    let #input_tuple_bindings = input;
+                    #storage_ident::#message_fn( &contract #( , #input_bindings )* )
+                });
+                (None, message_result)
+            } else {
+                let message_result = quote_spanned!(message_span=>
+                    &#message_callable(&mut contract, input)
+                );
+                (Some(quote! { mut }), message_result)
+            };
 
             quote_spanned!(message_span=>
                 Self::#message_ident(input) => {
@@ -757,18 +805,18 @@ impl Dispatch<'_> {
                         mutates: #mutates_storage,
                         dynamic_storage_alloc: #is_dynamic_storage_allocation_enabled,
                     };
-                    let mut contract: ::core::mem::ManuallyDrop<#storage_ident> =
+                    let #mut_token contract: ::core::mem::ManuallyDrop<#storage_ident> =
                         ::core::mem::ManuallyDrop::new(
                             ::ink_lang::codegen::initiate_message::<#storage_ident>(config)?
                         );
-                    let result: #message_output = #message_callable(&mut contract, input);
+                    let result: &#message_output = #message_result;
                     let failure = ::ink_lang::is_result_type!(#message_output)
-                        && ::ink_lang::is_result_err!(result);
+                        && ::ink_lang::is_result_err!(*result);
                     ::ink_lang::codegen::finalize_message::<#storage_ident, #message_output>(
                         !failure,
                         &contract,
                         config,
-                        &result,
+                        result,
                     )?;
                     ::core::result::Result::Ok(())
                 }
@@ -158,7 +158,7 @@ impl ItemImpls<'_> {
                         >();
                     )
                 });
-                let message_output = message.output().map(|output_type| {
+                let message_output = message.encoded_output().map(|output_type| {
                     let span = output_type.span();
                     quote_spanned!(span=>
                         let _: () = ::ink_lang::codegen::utils::consume_type::<
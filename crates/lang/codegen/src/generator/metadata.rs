@@ -214,7 +214,7 @@ impl Metadata<'_> {
                 let mutates = message.receiver().is_ref_mut();
                 let ident = message.ident();
                 let args = message.inputs().map(Self::generate_dispatch_argument);
-                let ret_ty = Self::generate_return_type(message.output());
+                let ret_ty = Self::generate_return_type(message.encoded_output());
                 quote_spanned!(span =>
                     ::ink_metadata::MessageSpec::from_label(::core::stringify!(#ident))
                         .selector([
@@ -174,6 +174,38 @@ impl Message {
         Ok(())
     }
 
+    /// Ensures that an ink! message returning a reference only borrows from `&self`.
+    ///
+    /// # Errors
+    ///
+    /// - If the given Rust method returns a mutable reference.
+    /// - If the given Rust method returns a reference but has a `&mut self` receiver.
+    fn ensure_valid_reference_output(
+        method_item: &syn::ImplItemMethod,
+    ) -> Result<(), syn::Error> {
+        let ret_type = match &method_item.sig.output {
+            syn::ReturnType::Type(_arrow, ret_type) => ret_type,
+            syn::ReturnType::Default => return Ok(()),
+        };
+        if let syn::Type::Reference(type_ref) = &**ret_type {
+            if type_ref.mutability.is_some() {
+                return Err(format_err!(
+                    ret_type,
+                    "ink! messages must not return mutable references"
+                ))
+            }
+            if let Some(syn::FnArg::Receiver(receiver)) = method_item.sig.inputs.first() {
+                if receiver.mutability.is_some() {
+                    return Err(format_err!(
+                        ret_type,
+                        "ink! messages returning a reference must have a `&self` receiver"
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Sanitizes the attributes for the ink! message.
     ///
     /// Returns a tuple of ink! attributes and non-ink! attributes.
@@ -203,6 +235,7 @@ impl TryFrom<syn::ImplItemMethod> for Message {
         ensure_callable_invariants(&method_item, CallableKind::Message)?;
         Self::ensure_receiver_is_self_ref(&method_item)?;
         Self::ensure_not_return_self(&method_item)?;
+        Self::ensure_valid_reference_output(&method_item)?;
         let (ink_attrs, other_attrs) = Self::sanitize_attributes(&method_item)?;
         let is_payable = ink_attrs.is_payable();
         let selector = ink_attrs.selector();
@@ -294,6 +327,24 @@ impl Message {
         }
     }
 
+    /// Returns `true` if the ink! message returns a reference.
+    pub fn returns_ref(&self) -> bool {
+        matches!(self.output(), Some(syn::Type::Reference(_)))
+    }
+
+    /// Returns the type that is encoded as the return value of the ink! message if any.
+    ///
+    /// # Note
+    ///
+    /// For ink! messages returning a reference `&V` this is the referenced type `V`
+    /// since the referent is encoded in place. Otherwise this is the return type.
+    pub fn encoded_output(&self) -> Option<&syn::Type> {
+        match self.output() {
+            Some(syn::Type::Reference(type_ref)) => Some(&type_ref.elem),
+            output => output,
+        }
+    }
+
     /// Returns a local ID unique to the ink! message with respect to its implementation block.
     ///
     /// # Note
@@ -347,6 +398,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn encoded_output_works() {
+        let test_inputs: Vec<(bool, Option<syn::Type>, syn::ImplItemMethod)> = vec![
+            (
+                // No output:
+                false,
+                None,
+                syn::parse_quote! {
+                    #[ink(message)]
+                    fn my_message(&self) {}
+                },
+            ),
+            (
+                // Owned output:
+                false,
+                Some(syn::parse_quote! { i32 }),
+                syn::parse_quote! {
+                    #[ink(message)]
+                    fn my_message(&self) -> i32 {}
+                },
+            ),
+            (
+                // Reference output:
+                true,
+                Some(syn::parse_quote! { (i32, u64) }),
+                syn::parse_quote! {
+                    #[ink(message)]
+                    fn my_message(&self) -> &(i32, u64) {}
+                },
+            ),
+        ];
+        for (returns_ref, expected_output, item_method) in test_inputs {
+            let message = <ir::Message as TryFrom<_>>::try_from(item_method).unwrap();
+            assert_eq!(message.returns_ref(), returns_ref);
+            assert_eq!(message.encoded_output().cloned(), expected_output);
+        }
+    }
+
     #[test]
     fn inputs_works() {
         macro_rules! expected_inputs {
@@ -571,6 +660,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn try_from_invalid_reference_output_fails() {
+        assert_try_from_fails(
+            syn::parse_quote! {
+                #[ink(message)]
+                fn my_message(&self) -> &mut i32 {}
+            },
+            "ink! messages must not return mutable references",
+        );
+        assert_try_from_fails(
+            syn::parse_quote! {
+                #[ink(message)]
+                fn my_message(&mut self) -> &i32 {}
+            },
+            "ink! messages returning a reference must have a `&self` receiver",
+        );
+    }
+
     #[test]
     fn try_from_generics_fails() {
         let item_methods: Vec<syn::ImplItemMethod> = vec![
@@ -279,6 +279,12 @@ impl TryFrom<syn::ItemImpl> for ItemImpl {
                         "message",
                         is_trait_impl,
                     )?;
+                    if is_trait_impl && message.returns_ref() {
+                        return Err(format_err!(
+                            message.item.sig.output,
+                            "ink! messages in trait impl blocks must not return references",
+                        ))
+                    }
                 }
                 ir::ImplItem::Constructor(constructor) => {
                     ensure_valid_visibility(
@@ -207,6 +207,19 @@ fn visibility_fails() {
     );
 }
 
+#[test]
+fn trait_message_reference_output_fails() {
+    assert_try_from_item_impl_fails(
+        syn::parse_quote! {
+            impl MyTrait for MyStorage {
+                #[ink(message)]
+                fn my_message(&self) -> &i32 {}
+            }
+        },
+        "ink! messages in trait impl blocks must not return references",
+    );
+}
+
 #[test]
 fn try_from_works() {
     let item_impls: Vec<syn::ItemImpl> = vec![
@@ -107,6 +107,41 @@ where
         ink_env::caller::<T>()
     }
 
+    /// Returns `true` if the caller of the executed contract is the origin
+    /// of the call stack, i.e. a plain account and not another contract.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ink_lang as ink;
+    /// #
+    /// # #[ink::contract]
+    /// # pub mod my_contract {
+    /// #     #[ink(storage)]
+    /// #     pub struct MyContract { }
+    /// #
+    /// #     impl MyContract {
+    /// #         #[ink(constructor)]
+    /// #         pub fn new() -> Self {
+    /// #             Self {}
+    /// #         }
+    /// #
+    /// #[ink(message)]
+    /// pub fn only_accounts(&self) {
+    ///     assert!(self.env().caller_is_origin(), "contracts may not call this");
+    /// }
+    /// #
+    /// #     }
+    /// # }
+    /// ```
+    ///
+    /// # Note
+    ///
+    /// For more details visit: [`ink_env::caller_is_origin`]
+    pub fn caller_is_origin(self) -> bool {
+        ink_env::caller_is_origin::<T>()
+    }
+
     /// Returns the transferred value for the contract execution.
     ///
     /// # Example
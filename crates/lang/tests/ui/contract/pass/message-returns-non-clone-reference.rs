@@ -0,0 +1,63 @@
+use contract::{
+    Contract,
+    Ledger,
+};
+use ink_lang as ink;
+use ink_lang::{
+    reflect::DispatchableMessageInfo,
+    selector_id,
+};
+
+#[ink::contract]
+mod contract {
+    use ink_storage::traits::{
+        PackedLayout,
+        SpreadLayout,
+        StorageLayout,
+    };
+
+    #[ink(storage)]
+    pub struct Contract {
+        ledger: Ledger,
+    }
+
+    /// Deliberately does not implement `Clone`.
+    #[derive(
+        Debug,
+        PartialEq,
+        Eq,
+        SpreadLayout,
+        PackedLayout,
+        StorageLayout,
+        scale::Encode,
+        scale::Decode,
+        scale_info::TypeInfo,
+    )]
+    pub struct Ledger {
+        pub entries: u32,
+    }
+
+    impl Contract {
+        #[ink(constructor)]
+        pub fn constructor(entries: u32) -> Self {
+            Self {
+                ledger: Ledger { entries },
+            }
+        }
+
+        #[ink(message)]
+        pub fn ledger(&self) -> &Ledger {
+            &self.ledger
+        }
+    }
+}
+
+fn main() {
+    let mut contract = Contract::constructor(7);
+    assert_eq!(contract.ledger(), &Ledger { entries: 7 });
+
+    // The reflected callable yields an owned copy of the referent without cloning it.
+    let callable =
+        <Contract as DispatchableMessageInfo<{ selector_id!("ledger") }>>::CALLABLE;
+    assert_eq!(callable(&mut contract, ()), Ledger { entries: 7 });
+}
@@ -0,0 +1,59 @@
+use contract::Contract;
+use ink_lang as ink;
+
+#[ink::trait_definition]
+pub trait Flip {
+    #[ink(message)]
+    fn flip(&mut self);
+
+    #[ink(message)]
+    fn get(&self) -> bool;
+}
+
+#[ink::contract]
+mod contract {
+    use super::Flip;
+
+    #[ink(storage)]
+    pub struct Contract {
+        value: bool,
+        count: u32,
+    }
+
+    // The trait implementation precedes the inherent implementation so that
+    // the source order of the messages differs from their dispatch order.
+    impl Flip for Contract {
+        #[ink(message)]
+        fn flip(&mut self) {
+            self.value = !self.value;
+            self.count += 1;
+        }
+
+        #[ink(message)]
+        fn get(&self) -> bool {
+            self.value
+        }
+    }
+
+    impl Contract {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                value: false,
+                count: 0,
+            }
+        }
+
+        #[ink(message)]
+        pub fn count(&self) -> &u32 {
+            &self.count
+        }
+    }
+}
+
+fn main() {
+    let mut contract = Contract::new();
+    <Contract as Flip>::flip(&mut contract);
+    assert!(<Contract as Flip>::get(&contract));
+    assert_eq!(contract.count(), &1);
+}
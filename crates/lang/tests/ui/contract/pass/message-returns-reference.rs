@@ -0,0 +1,87 @@
+use contract::{
+    Contract,
+    Point,
+};
+use ink_lang as ink;
+use ink_lang::{
+    reflect::DispatchableMessageInfo,
+    selector_id,
+};
+use scale::{
+    Decode,
+    Encode,
+};
+
+#[ink::contract]
+mod contract {
+    use ink_storage::traits::{
+        PackedLayout,
+        SpreadLayout,
+        StorageLayout,
+    };
+
+    #[ink(storage)]
+    pub struct Contract {
+        point: Point,
+    }
+
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        SpreadLayout,
+        PackedLayout,
+        StorageLayout,
+        scale::Encode,
+        scale::Decode,
+        scale_info::TypeInfo,
+    )]
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+
+    impl Contract {
+        #[ink(constructor)]
+        pub fn constructor(x: i32, y: i32) -> Self {
+            Self {
+                point: Point { x, y },
+            }
+        }
+
+        #[ink(message)]
+        pub fn point(&self) -> &Point {
+            &self.point
+        }
+
+        #[ink(message)]
+        pub fn coordinate(&self, first: bool) -> &i32 {
+            if first {
+                &self.point.x
+            } else {
+                &self.point.y
+            }
+        }
+    }
+}
+
+type PointOutput = <Contract as DispatchableMessageInfo<{ selector_id!("point") }>>::Output;
+
+fn main() {
+    let mut contract = Contract::constructor(1, -2);
+    let point = Point { x: 1, y: -2 };
+    assert_eq!(contract.point(), &point);
+    assert_eq!(contract.coordinate(false), &-2);
+
+    // The referent is encoded in place and decoded as the owned type by callers.
+    let encoded = Encode::encode(contract.point());
+    assert_eq!(encoded, point.encode());
+    let decoded = <PointOutput as Decode>::decode(&mut &encoded[..]).unwrap();
+    assert_eq!(decoded, point);
+
+    // The reflected callable yields an owned copy of the referent.
+    let callable =
+        <Contract as DispatchableMessageInfo<{ selector_id!("coordinate") }>>::CALLABLE;
+    assert_eq!(callable(&mut contract, true), 1);
+}
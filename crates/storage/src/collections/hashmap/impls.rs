@@ -14,6 +14,7 @@
 
 use super::{
     HashMap as StorageHashMap,
+    IntoIter,
     Iter,
     IterMut,
 };
@@ -72,7 +73,7 @@ where
     type Output = V;
 
     fn index(&self, index: &Q) -> &Self::Output {
-        self.get(index).expect("index out of bounds")
+        self.get(index).expect("no entry found for key")
     }
 }
 
@@ -85,7 +86,30 @@ where
     Key: From<<H as HashOutput>::Type>,
 {
     fn index_mut(&mut self, index: &Q) -> &mut Self::Output {
-        self.get_mut(index).expect("index out of bounds")
+        self.get_mut(index).expect("no entry found for key")
+    }
+}
+
+impl<K, V, H> IntoIterator for StorageHashMap<K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, H>;
+
+    /// Consumes the hash map and yields all of its key/value pairs.
+    ///
+    /// # Note
+    ///
+    /// The storage cell of every value is cleared as the iterator advances.
+    /// Once the iterator is dropped the storage held by the keys and by any
+    /// pairs that have not been yielded is cleared as well, so the consumed
+    /// hash map leaves no storage behind.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
     }
 }
 
@@ -153,6 +177,27 @@ where
     }
 }
 
+/// Clones the key/value pairs of the hash map into a fresh in-memory hash map.
+///
+/// # Note
+///
+/// The clone is independent of the original: it is not associated with the
+/// storage key of the original and does not alias its contract storage.
+/// The clone yields its pairs in the same order as the original.
+impl<K, V, H> Clone for StorageHashMap<K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: Clone + PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    fn clone(&self) -> Self {
+        self.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
 impl<K, V, H> PartialEq for StorageHashMap<K, V, H>
 where
     K: Ord + Clone + PackedLayout,
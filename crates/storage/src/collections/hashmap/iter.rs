@@ -29,6 +29,9 @@ use ink_env::hash::{
 use ink_primitives::Key;
 
 /// An iterator over shared references to the elements of a storage hash map.
+///
+/// Iterating in reverse yields the elements in the reverse order of the
+/// internal storage of the keys.
 #[derive(Debug, Copy, Clone)]
 pub struct Iter<'a, K, V, H>
 where
@@ -418,3 +421,160 @@ where
         self.iter.next_back()
     }
 }
+
+/// A draining iterator over the elements of a storage hash map.
+///
+/// Every yielded key/value pair is removed from the storage hash map.
+#[derive(Debug)]
+pub struct Drain<'a, K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    /// The storage hash map that is drained.
+    hash_map: &'a mut StorageHashMap<K, V, H>,
+    /// The index of the next key entry to inspect.
+    next_index: u32,
+}
+
+impl<'a, K, V, H> Drain<'a, K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    /// Creates a new draining iterator for the given storage hash map.
+    pub(crate) fn new(hash_map: &'a mut StorageHashMap<K, V, H>) -> Self {
+        Self {
+            hash_map,
+            next_index: 0,
+        }
+    }
+}
+
+impl<'a, K, V, H> Iterator for Drain<'a, K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next_index < self.hash_map.keys.capacity() {
+            let index = self.next_index;
+            self.next_index += 1;
+            // Taking a vacant key entry yields `None` so that it is skipped.
+            if let Some(key) = self.hash_map.keys.take(index) {
+                let entry = self
+                    .hash_map
+                    .values
+                    .put_get(&key, None)
+                    .expect("a key in the stash must have a value");
+                return Some((key, entry.value))
+            }
+        }
+        // Free the storage of the vacant key entries left behind once the
+        // hash map has been drained completely.
+        self.hash_map.defrag(None);
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.hash_map.len() as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, K, V, H> ExactSizeIterator for Drain<'a, K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+}
+
+/// An owning iterator over the elements of a storage hash map.
+///
+/// Every yielded key/value pair is removed from the storage hash map and the
+/// storage cell of its value is cleared.
+#[derive(Debug)]
+pub struct IntoIter<K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    /// The storage hash map that is consumed.
+    hash_map: StorageHashMap<K, V, H>,
+    /// The index of the next key entry to inspect.
+    next_index: u32,
+}
+
+impl<K, V, H> IntoIter<K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    /// Creates a new owning iterator for the given storage hash map.
+    pub(crate) fn new(hash_map: StorageHashMap<K, V, H>) -> Self {
+        Self {
+            hash_map,
+            next_index: 0,
+        }
+    }
+}
+
+impl<K, V, H> Iterator for IntoIter<K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next_index < self.hash_map.keys.capacity() {
+            let index = self.next_index;
+            self.next_index += 1;
+            // Taking a vacant key entry yields `None` so that it is skipped.
+            if let Some(key) = self.hash_map.keys.take(index) {
+                let entry = self
+                    .hash_map
+                    .values
+                    .put_get(&key, None)
+                    .expect("a key in the stash must have a value");
+                // The value is moved out of the hash map, so only its own cell
+                // is cleared while any storage it refers to is owned by the caller.
+                if let Some(root_key) = self.hash_map.values.key_at(&key) {
+                    ink_env::clear_contract_storage(&root_key);
+                }
+                return Some((key, entry.value))
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.hash_map.len() as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<K, V, H> ExactSizeIterator for IntoIter<K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+}
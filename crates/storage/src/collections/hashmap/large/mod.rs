@@ -0,0 +1,266 @@
+// Copyright 2018-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A storage hash map that indexes its keys with `u64` instead of `u32`.
+
+mod storage;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    lazy::LazyHashMap,
+    traits::PackedLayout,
+    Lazy,
+};
+use core::borrow::Borrow;
+use ink_env::hash::{
+    Blake2x256,
+    CryptoHash,
+    HashOutput,
+};
+use ink_prelude::borrow::ToOwned;
+use ink_primitives::Key;
+
+/// The index type within a large hash map.
+///
+/// # Note
+///
+/// Used for key indices internal to the large hash map.
+type KeyIndex = u64;
+
+/// A hash map operating on the contract storage that may hold more than
+/// `u32::MAX` keys.
+///
+/// Offers the core API of [`HashMap`](`super::HashMap`) but indexes its keys
+/// with `u64` instead of `u32`.
+///
+/// # Storage Cost
+///
+/// A contiguous region of `2^64` cells is not representable, so unlike
+/// [`HashMap`](`super::HashMap`) the keys are not held in a `Stash` but are
+/// addressed by the hash of their index. This costs one extra hash for every
+/// key access, and every value entry encodes an 8 byte instead of a 4 byte key
+/// index. Prefer [`HashMap`](`super::HashMap`) unless the number of keys can
+/// actually exceed `u32::MAX`.
+///
+/// The keys are kept dense: taking a key moves the last key into its slot, so
+/// there are no vacant slots to defragment.
+#[derive(Debug)]
+pub struct LargeHashMap<K, V, H = Blake2x256>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    /// The number of key/value pairs stored in the map.
+    len: Lazy<KeyIndex>,
+    /// The keys of the map addressed by their index.
+    keys: LazyHashMap<KeyIndex, K, H>,
+    /// The values of the map.
+    values: LazyHashMap<K, ValueEntry<V>, H>,
+}
+
+/// An entry within the large storage hash map.
+///
+/// Stores the value as well as the index to its associated key.
+#[derive(Debug, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+struct ValueEntry<V> {
+    /// The value stored in this entry.
+    value: V,
+    /// The index of the key associated with this value.
+    key_index: KeyIndex,
+}
+
+impl<K, V, H> LargeHashMap<K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    /// Creates a new empty large storage hash map.
+    pub fn new() -> Self {
+        Self {
+            len: Lazy::new(0),
+            keys: LazyHashMap::new(),
+            values: LazyHashMap::new(),
+        }
+    }
+
+    /// Returns the number of key/value pairs stored in the map.
+    pub fn len(&self) -> u64 {
+        *self.len
+    }
+
+    /// Returns `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the key stored at the given index if any.
+    ///
+    /// # Note
+    ///
+    /// Keys occupy the indices `0..len` but taking a key may move another key
+    /// into its index.
+    pub fn key_at(&self, index: u64) -> Option<&K> {
+        self.keys.get(&index)
+    }
+
+    fn clear_cells(&self) {
+        if self.values.key().is_none() {
+            // We won't clear any storage if we are in lazy state since there
+            // probably has not been any state written to storage, yet.
+            return
+        }
+        for index in 0..self.len() {
+            if let Some(key) = self.keys.get(&index) {
+                self.values.clear_packed_at(key);
+            }
+            self.keys.clear_packed_at(&index);
+        }
+    }
+}
+
+impl<K, V, H> LargeHashMap<K, V, H>
+where
+    K: Ord + Eq + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    /// Inserts a key-value pair into the map.
+    ///
+    /// Returns the previous value associated with the same key if any.
+    /// If the map did not have this key present, `None` is returned.
+    ///
+    /// # Panics
+    ///
+    /// If the map already holds `u64::MAX` keys.
+    pub fn insert(&mut self, key: K, new_value: V) -> Option<V> {
+        if let Some(occupied) = self.values.get_mut(&key) {
+            // Update value, don't update key.
+            let old_value = core::mem::replace(&mut occupied.value, new_value);
+            return Some(old_value)
+        }
+        // At this point we know that `key` does not yet exist in the map.
+        let key_index = self.len();
+        *self.len = key_index
+            .checked_add(1)
+            .expect("cannot store more than u64::MAX keys");
+        self.keys.put(key_index, Some(key.to_owned()));
+        self.values.put(
+            key,
+            Some(ValueEntry {
+                value: new_value,
+                key_index,
+            }),
+        );
+        None
+    }
+
+    /// Removes the key/value pair from the map associated with the given key.
+    ///
+    /// - Returns the removed value if any.
+    ///
+    /// # Note
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn take<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        let entry = self.values.put_get(key, None)?;
+        let last_index = self.len() - 1;
+        let last_key = self
+            .keys
+            .put_get(&last_index, None)
+            .expect("a key must be stored at every index below the length");
+        if entry.key_index != last_index {
+            // Move the last key into the freed index so that the keys stay dense.
+            self.values
+                .get_mut::<K>(&last_key)
+                .expect("every stored key must have an associated value")
+                .key_index = entry.key_index;
+            self.keys.put(entry.key_index, Some(last_key));
+        }
+        *self.len = last_index;
+        Some(entry.value)
+    }
+
+    /// Returns a shared reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        self.values.get(key).map(|entry| &entry.value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        self.values.get_mut(key).map(|entry| &mut entry.value)
+    }
+
+    /// Returns `true` if there is an entry corresponding to the key in the map.
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        self.values.get(key).is_some()
+    }
+}
+
+impl<K, V, H> Drop for LargeHashMap<K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    fn drop(&mut self) {
+        self.clear_cells();
+    }
+}
+
+impl<K, V, H> Default for LargeHashMap<K, V, H>
+where
+    K: Ord + Clone + PackedLayout,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2018-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::LargeHashMap;
+use crate::traits::{
+    KeyPtr,
+    SpreadLayout,
+};
+use ink_primitives::Key;
+
+/// Returns always the same `KeyPtr`.
+fn key_ptr() -> KeyPtr {
+    let root_key = Key::from([0x42; 32]);
+    KeyPtr::from(root_key)
+}
+
+/// Pushes a `LargeHashMap` instance into the contract storage.
+fn push_hmap(hmap: &LargeHashMap<u8, i32>) {
+    SpreadLayout::push_spread(hmap, &mut key_ptr());
+}
+
+/// Pulls a `LargeHashMap` instance from the contract storage.
+fn pull_hmap() -> LargeHashMap<u8, i32> {
+    <LargeHashMap<u8, i32> as SpreadLayout>::pull_spread(&mut key_ptr())
+}
+
+fn filled_hmap() -> LargeHashMap<u8, i32> {
+    let mut hmap = LargeHashMap::new();
+    for (key, value) in [(b'A', 1), (b'B', 2), (b'C', 3), (b'D', 4)] {
+        assert_eq!(hmap.insert(key, value), None);
+    }
+    hmap
+}
+
+#[test]
+fn new_works() {
+    let hmap = <LargeHashMap<u8, i32>>::new();
+    assert!(hmap.is_empty());
+    assert_eq!(hmap.len(), 0u64);
+    assert_eq!(hmap.get(&b'A'), None);
+    assert_eq!(hmap.key_at(0), None);
+}
+
+#[test]
+fn insert_and_read_back_works() {
+    let mut hmap = filled_hmap();
+    assert_eq!(hmap.len(), 4u64);
+    assert_eq!(hmap.get(&b'A'), Some(&1));
+    assert_eq!(hmap.get(&b'D'), Some(&4));
+    assert_eq!(hmap.get(&b'E'), None);
+    assert!(hmap.contains_key(&b'B'));
+    assert!(!hmap.contains_key(&b'E'));
+    // Overwriting a value returns the old one and keeps the key.
+    assert_eq!(hmap.insert(b'B', 20), Some(2));
+    assert_eq!(hmap.get(&b'B'), Some(&20));
+    assert_eq!(hmap.len(), 4u64);
+    *hmap.get_mut(&b'C').unwrap() += 10;
+    assert_eq!(hmap.get(&b'C'), Some(&13));
+    let keys = (0..hmap.len())
+        .map(|index| *hmap.key_at(index).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, b"ABCD");
+}
+
+#[test]
+fn take_keeps_keys_dense() {
+    let mut hmap = filled_hmap();
+    assert_eq!(hmap.take(&b'B'), Some(2));
+    assert_eq!(hmap.take(&b'B'), None);
+    assert_eq!(hmap.len(), 3u64);
+    // The last key has been moved into the freed index.
+    assert_eq!(hmap.key_at(1), Some(&b'D'));
+    assert_eq!(hmap.key_at(3), None);
+    assert_eq!(hmap.take(&b'A'), Some(1));
+    assert_eq!(hmap.take(&b'D'), Some(4));
+    assert_eq!(hmap.take(&b'C'), Some(3));
+    assert!(hmap.is_empty());
+    // Moved keys still resolve to their values.
+    assert_eq!(hmap.insert(b'E', 5), None);
+    assert_eq!(hmap.key_at(0), Some(&b'E'));
+    assert_eq!(hmap.get(&b'E'), Some(&5));
+}
+
+#[test]
+fn spread_layout_push_pull_works() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        let mut hmap = filled_hmap();
+        assert_eq!(hmap.take(&b'A'), Some(1));
+        push_hmap(&hmap);
+        let pulled = pull_hmap();
+        assert_eq!(pulled.len(), 3u64);
+        assert_eq!(pulled.get(&b'A'), None);
+        assert_eq!(pulled.get(&b'B'), Some(&2));
+        assert_eq!(pulled.get(&b'C'), Some(&3));
+        assert_eq!(pulled.get(&b'D'), Some(&4));
+        assert_eq!(pulled.key_at(0), Some(&b'D'));
+        Ok(())
+    })
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn clear_spread_frees_storage() -> ink_env::Result<()> {
+    use ink_env::DefaultEnvironment;
+    ink_env::test::run_test::<DefaultEnvironment, _>(|_| {
+        let contract = ink_env::test::get_current_contract_account_id::<
+            DefaultEnvironment,
+        >()?;
+        let used_cells =
+            || ink_env::test::count_used_storage_cells::<DefaultEnvironment>(&contract);
+        push_hmap(&filled_hmap());
+        assert!(used_cells()? > 0);
+        let hmap = pull_hmap();
+        SpreadLayout::clear_spread(&hmap, &mut key_ptr());
+        assert_eq!(used_cells()?, 0);
+        Ok(())
+    })
+}
@@ -16,6 +16,7 @@
 
 mod impls;
 mod iter;
+mod large;
 mod storage;
 
 #[cfg(test)]
@@ -24,7 +25,10 @@ mod tests;
 #[cfg(all(test, feature = "ink-fuzz-tests"))]
 mod fuzz_tests;
 
+pub use self::large::LargeHashMap;
 pub use self::iter::{
+    Drain,
+    IntoIter,
     Iter,
     IterMut,
     Keys,
@@ -44,13 +48,17 @@ use crate::{
 use core::{
     borrow::Borrow,
     cmp::Eq,
+    iter::Rev,
 };
 use ink_env::hash::{
     Blake2x256,
     CryptoHash,
     HashOutput,
 };
-use ink_prelude::borrow::ToOwned;
+use ink_prelude::{
+    borrow::ToOwned,
+    vec::Vec,
+};
 use ink_primitives::Key;
 
 /// The index type within a hashmap.
@@ -60,6 +68,36 @@ use ink_primitives::Key;
 /// Used for key indices internal to the hashmap.
 type KeyIndex = u32;
 
+/// The number of elements of a hash map exceeds a given bound.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BoundExceeded {
+    /// The actual number of elements stored in the hash map.
+    pub len: u32,
+}
+
+/// A summary of the occupation of the storage used by a hash map.
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Eq)]
+pub struct MapSummary {
+    /// The number of key/value pairs stored in the hash map.
+    pub len: u32,
+    /// The number of slots of the storage holding the keys.
+    pub capacity: u32,
+    /// The number of vacant slots that can be freed by defragmentation.
+    pub vacant: u32,
+}
+
+/// A value exists in the contract storage but cannot be decoded.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DecodeError;
+
+/// The key passed to [`HashMap::try_insert`] is already in the hash map.
+#[derive(Debug, PartialEq, Eq)]
+pub struct OccupiedError<V> {
+    /// The value that has not been inserted.
+    pub value: V,
+}
+
 /// A hash map operating on the contract storage.
 ///
 /// Stores a mapping between keys and values.
@@ -79,6 +117,15 @@ type KeyIndex = u32;
 ///
 /// Users should generally prefer using this storage hash map over the low-level
 /// `LazyHashMap` for direct usage in their smart contracts.
+///
+/// The keys are stored in a [`Stash`] indexed by `u32` which caps the number of
+/// slots held by the keys to `u32::MAX`. Inserting beyond this panics instead
+/// of silently wrapping around. Use [`LargeHashMap`] if more keys are needed.
+///
+/// The cell of a value is derived from the hash of its key and the storage key
+/// of the hash map, so distinct hash maps never share cells. The hash function
+/// `H` is stateless and only selected by its type, using another `H` yields
+/// different cells for the same keys.
 #[derive(Debug)]
 pub struct HashMap<K, V, H = Blake2x256>
 where
@@ -175,6 +222,45 @@ where
         self.keys.is_empty()
     }
 
+    /// Returns the number of key-value pairs the hash map can hold before
+    /// the storage of its keys has to grow.
+    pub fn capacity(&self) -> u32 {
+        self.keys.capacity()
+    }
+
+    /// Ensures that at least `additional` more key-value pairs can be inserted
+    /// before the storage of the keys has to grow.
+    ///
+    /// # Note
+    ///
+    /// The reserved storage is freed again by [`HashMap::defrag`] unless it
+    /// has been filled up in the meantime.
+    ///
+    /// # Panics
+    ///
+    /// If the capacity would exceed `u32::MAX`.
+    pub fn reserve(&mut self, additional: u32) {
+        self.keys.reserve(additional)
+    }
+
+    /// Shrinks the capacity of the hash map down to `min_capacity`, but never
+    /// below its length.
+    ///
+    /// Does nothing if the capacity is already at most `min_capacity`.
+    ///
+    /// # Note
+    ///
+    /// This defragments the storage of the keys just far enough to free the
+    /// excess storage cells, see [`HashMap::defrag`].
+    pub fn shrink_to(&mut self, min_capacity: u32) {
+        let target = core::cmp::max(min_capacity, self.len());
+        if let Some(excess) = self.capacity().checked_sub(target) {
+            // Every defrag iteration frees exactly one cell as long as there
+            // are vacant entries left, which holds since `target >= len`.
+            self.defrag(Some(excess));
+        }
+    }
+
     /// Returns an iterator yielding shared references to all key/value pairs
     /// of the hash map.
     ///
@@ -183,6 +269,9 @@ where
     /// - Avoid unbounded iteration over big storage hash maps.
     /// - Prefer using methods like `Iterator::take` in order to limit the number
     ///   of yielded elements.
+    /// - The pairs are yielded in the order of the internal storage of the keys.
+    ///   Reversing the iterator walks that storage from its high end, so "reverse"
+    ///   refers to the reverse of this internal order and not to any key order.
     pub fn iter(&self) -> Iter<K, V, H> {
         Iter::new(self)
     }
@@ -231,6 +320,165 @@ where
     pub fn keys(&self) -> Keys<K> {
         Keys::new(self)
     }
+
+    /// Returns an iterator yielding shared references to all keys of the hash map.
+    ///
+    /// # Note
+    ///
+    /// Guaranteed to only read the storage of the keys and to never load any
+    /// value, e.g. for exporting the members of a set. This is the same as
+    /// [`HashMap::keys`] which does not load values either.
+    pub fn key_set(&self) -> Keys<K> {
+        self.keys()
+    }
+
+    /// Returns an iterator that removes and yields all key/value pairs of the hash map.
+    ///
+    /// # Note
+    ///
+    /// Every pair is removed as the iterator advances. Dropping the iterator
+    /// early leaves the pairs that have not been yielded in the hash map.
+    /// Once the iterator is exhausted the storage held by the keys is freed.
+    pub fn drain(&mut self) -> Drain<K, V, H> {
+        Drain::new(self)
+    }
+
+    /// Returns an iterator over the keys of the hash map yielding the most
+    /// recently inserted keys first.
+    ///
+    /// # Note
+    ///
+    /// - Keys are stored at increasing indices in insertion order.
+    ///   However, the slots of removed keys are reused by later insertions,
+    ///   so the order is only strictly newest-first if no key has been removed
+    ///   since the oldest yielded key has been inserted.
+    /// - Prefer using methods like `Iterator::take` in order to limit the number
+    ///   of yielded elements.
+    pub fn keys_recent(&self) -> Rev<Keys<K>> {
+        self.keys().rev()
+    }
+
+    /// Returns all keys of the hash map together with the storage keys of their values.
+    ///
+    /// # Note
+    ///
+    /// Meant for migration tests that snapshot the derived storage keys in order
+    /// to detect layout changes across versions. A hash map that has not yet been
+    /// associated with a storage location yields no entries.
+    #[cfg(feature = "std")]
+    pub fn debug_cell_keys(&self) -> Vec<(K, Key)> {
+        self.keys()
+            .filter_map(|key| {
+                self.values
+                    .key_at(key)
+                    .map(|cell_key| (key.clone(), cell_key))
+            })
+            .collect()
+    }
+
+    /// Returns a summary of the storage occupied by the hash map.
+    ///
+    /// # Note
+    ///
+    /// Meant for storage inspection in order to tell whether the hash map
+    /// would benefit from a [`HashMap::defrag`]. Does not iterate over the
+    /// hash map.
+    #[cfg(feature = "std")]
+    pub fn debug_summary(&self) -> MapSummary {
+        let len = self.len();
+        let capacity = self.keys.capacity();
+        MapSummary {
+            len,
+            capacity,
+            vacant: capacity - len,
+        }
+    }
+
+    /// Collects clones of all keys of the hash map if there are at most `max` of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BoundExceeded`] carrying the actual number of keys if the
+    /// hash map stores more than `max` keys. No key is read in this case.
+    pub fn collect_keys_bounded(&self, max: u32) -> Result<Vec<K>, BoundExceeded> {
+        let len = self.len();
+        if len > max {
+            return Err(BoundExceeded { len })
+        }
+        Ok(self.keys().cloned().collect())
+    }
+
+    /// Returns clones of up to `limit` key/value pairs starting at the internal
+    /// key index `start_index` together with the index to continue reading from.
+    ///
+    /// The returned index is `None` if there are no more slots to read.
+    ///
+    /// # Note
+    ///
+    /// Vacant slots are skipped and do not count towards `limit`.
+    /// Paging through the whole map with the returned cursors yields every
+    /// entry exactly once as long as the map is not modified in between.
+    /// The final page might be empty if only vacant slots remain.
+    pub fn read_page(&self, start_index: u32, limit: u32) -> (Vec<(K, V)>, Option<u32>)
+    where
+        V: Clone,
+    {
+        let capacity = self.keys.capacity();
+        let mut page = Vec::new();
+        let mut index = start_index;
+        while index < capacity && (page.len() as u32) < limit {
+            if let Some(key) = self.keys.get(index) {
+                let entry = self
+                    .values
+                    .get(key)
+                    .expect("a key in the stash must have a value");
+                page.push((key.clone(), entry.value.clone()));
+            }
+            index += 1;
+        }
+        let next = if index < capacity { Some(index) } else { None };
+        (page, next)
+    }
+
+    /// Returns the sum of `f` applied to all values of the hash map.
+    ///
+    /// # Note
+    ///
+    /// The values are projected by reference, so they are never cloned.
+    /// Still this loads every key and every value from the contract storage
+    /// that has not been loaded before, i.e. up to two storage reads per
+    /// key/value pair. Avoid this for big storage hash maps.
+    pub fn sum_by<B, F>(&self, f: F) -> B
+    where
+        F: FnMut(&V) -> B,
+        B: core::iter::Sum,
+    {
+        self.values().map(f).sum()
+    }
+
+    /// Returns `true` if both hash maps are equal apart from the entries whose
+    /// keys are in `ignore`.
+    ///
+    /// # Note
+    ///
+    /// The ignored keys may be present in either, both or none of the hash maps
+    /// and may map to different values. Like the `PartialEq` implementation this
+    /// loads every key of both hash maps and every value of `self` that is not
+    /// ignored.
+    pub fn eq_ignoring(&self, other: &Self, ignore: &[K]) -> bool
+    where
+        V: PartialEq,
+    {
+        let considered = |hash_map: &Self| {
+            hash_map.keys().filter(|key| !ignore.contains(key)).count()
+        };
+        if considered(self) != considered(other) {
+            return false
+        }
+        self.iter()
+            .filter(|(key, _)| !ignore.contains(key))
+            .all(|(key, value)| other.get(key) == Some(value))
+    }
 }
 
 impl<K, V, H> HashMap<K, V, H>
@@ -294,6 +542,33 @@ where
         None
     }
 
+    /// Inserts a key-value pair into the map.
+    ///
+    /// Returns `true` if the map did not have this key present and `false` if the
+    /// value of an existing key has been overwritten.
+    ///
+    /// # Note
+    ///
+    /// The overwritten value is dropped instead of being returned.
+    pub fn insert_new(&mut self, key: K, value: V) -> bool {
+        self.insert(key, value).is_none()
+    }
+
+    /// Inserts a key-value pair into the map if the key is not yet present.
+    ///
+    /// Returns a mutable reference to the inserted value.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value that has not been inserted if the map already has
+    /// this key present. The existing value is left untouched.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<&mut V, OccupiedError<V>> {
+        match self.entry(key) {
+            Entry::Occupied(_) => Err(OccupiedError { value }),
+            Entry::Vacant(vacant) => Ok(vacant.insert(value)),
+        }
+    }
+
     /// Removes the key/value pair from the map associated with the given key.
     ///
     /// - Returns the removed value if any.
@@ -303,15 +578,168 @@ where
     /// The key may be any borrowed form of the map's key type,
     /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
     pub fn take<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes the key/value pair from the map associated with the given key.
+    ///
+    /// - Returns `true` if the key was present in the map.
+    ///
+    /// # Note
+    ///
+    /// Prefer this over [`HashMap::take`] if you are not interested in the
+    /// removed value. The removed key is not loaded from the contract storage.
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn remove<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        let key_index = match self.values.get(key) {
+            Some(entry) => entry.key_index,
+            None => return false,
+        };
+        // SAFETY: The `key_index` of a value entry always refers to the
+        //         occupied key entry of its associated key.
+        unsafe { self.keys.remove_occupied(key_index) }
+            .expect("`key_index` must point to a valid key entry");
+        self.values.put(key.to_owned(), None);
+        true
+    }
+
+    /// Removes the key/value pair from the map associated with the given key.
+    ///
+    /// - Returns the stored key together with the removed value if any.
+    ///
+    /// # Note
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
     where
         K: Borrow<Q>,
         Q: Ord + scale::Encode + ToOwned<Owned = K>,
     {
         let entry = self.values.put_get(key, None)?;
-        self.keys
+        let stored_key = self
+            .keys
             .take(entry.key_index)
             .expect("`key_index` must point to a valid key entry");
-        Some(entry.value)
+        Some((stored_key, entry.value))
+    }
+
+    /// Removes the key/value pair from the map associated with the given key
+    /// and defragments the storage freed this way.
+    ///
+    /// - Returns the removed value if any.
+    ///
+    /// # Note
+    ///
+    /// This runs [`HashMap::defrag`] with a limit of a single iteration after
+    /// every successful removal which keeps the storage used by the keys
+    /// compact at a bounded cost per call.
+    pub fn take_and_defrag<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        let value = self.take(key)?;
+        self.defrag(Some(1));
+        Some(value)
+    }
+
+    /// Removes the key/value pair from the map associated with the given key
+    /// and reports the key relocated by defragmenting the storage freed this way.
+    ///
+    /// - Returns the removed value if any.
+    /// - Returns the key that has been moved to another key index together with
+    ///   its new index if the removal triggered such a relocation.
+    ///
+    /// # Note
+    ///
+    /// Like [`HashMap::take_and_defrag`] this performs a single defragmentation
+    /// step after a successful removal. Useful for external indices that keep
+    /// track of the key indices as returned by [`OccupiedEntry::key_index`].
+    pub fn take_reporting<Q>(&mut self, key: &Q) -> (Option<V>, Option<(K, u32)>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        let value = match self.take(key) {
+            Some(value) => value,
+            None => return (None, None),
+        };
+        let mut relocated = None;
+        let values = &mut self.values;
+        let callback = |old_index, new_index, moved: &K| {
+            let value_entry = values.get_mut::<K>(moved).expect("key must be valid");
+            debug_assert_eq!(value_entry.key_index, old_index);
+            value_entry.key_index = new_index;
+            relocated = Some((moved.to_owned(), new_index));
+        };
+        self.keys.defrag(Some(1), callback);
+        (Some(value), relocated)
+    }
+
+    /// Removes the key/value pair from the map associated with the given key
+    /// if `pred` returns `true` for its value.
+    ///
+    /// - Returns the removed value if any.
+    /// - Returns `None` and leaves the map untouched if the key is missing or
+    ///   `pred` returns `false`.
+    ///
+    /// # Note
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn remove_if<Q, F>(&mut self, key: &Q, pred: F) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+        F: FnOnce(&V) -> bool,
+    {
+        if !pred(self.get(key)?) {
+            return None
+        }
+        self.take(key)
+    }
+
+    /// Swaps the values associated with the keys `a` and `b`.
+    ///
+    /// - Returns `true` if both keys are present in the map.
+    /// - Returns `false` and leaves the map untouched if either key is missing.
+    ///
+    /// # Note
+    ///
+    /// Only the values are exchanged, the keys keep their key indices.
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn swap_values<Q>(&mut self, a: &Q, b: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        if self.get(a).is_none() || self.get(b).is_none() {
+            return false
+        }
+        if a == b {
+            return true
+        }
+        self.values.swap(a, b);
+        // Swapping the value entries has swapped their key indices as well,
+        // so they are swapped back in order to keep them pointing to their keys.
+        let a_index = self.values.get(a).expect("`a` must be present").key_index;
+        let b_entry = self.values.get_mut(b).expect("`b` must be present");
+        let b_index = core::mem::replace(&mut b_entry.key_index, a_index);
+        self.values.get_mut(a).expect("`a` must be present").key_index = b_index;
+        true
     }
 
     /// Returns a shared reference to the value corresponding to the key.
@@ -326,6 +754,43 @@ where
         self.values.get(key).map(|entry| &entry.value)
     }
 
+    /// Returns a shared reference to the value corresponding to the key or
+    /// `default` if the key is not in the map.
+    ///
+    /// # Note
+    ///
+    /// Neither copies the value nor inserts `default` into the map.
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn get_or<'a, Q>(&'a self, key: &Q, default: &'a V) -> &'a V
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Returns a shared reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError`] instead of panicking if the value exists in the
+    /// contract storage but cannot be decoded, e.g. after an incomplete migration.
+    pub fn try_get<Q>(&self, key: &Q) -> Result<Option<&V>, DecodeError>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        self.values
+            .try_get(key)
+            .map(|entry| entry.map(|entry| &entry.value))
+            .map_err(|_| DecodeError)
+    }
+
     /// Returns a mutable reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map's key type,
@@ -338,6 +803,69 @@ where
         self.values.get_mut(key).map(|entry| &mut entry.value)
     }
 
+    /// Returns the stored key and a shared reference to the value corresponding
+    /// to the key.
+    ///
+    /// # Note
+    ///
+    /// The stored key is resolved through the keys of the map and may differ
+    /// from the given key for types that can be `==` without being identical.
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        let entry = self.values.get(key)?;
+        let stored_key = self
+            .keys
+            .get(entry.key_index)
+            .expect("`key_index` must point to a valid key entry");
+        Some((stored_key, &entry.value))
+    }
+
+    /// Returns the stored key and a mutable reference to the value corresponding
+    /// to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn get_key_value_mut<Q>(&mut self, key: &Q) -> Option<(&K, &mut V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        let entry = self.values.get_mut(key)?;
+        let stored_key = self
+            .keys
+            .get(entry.key_index)
+            .expect("`key_index` must point to a valid key entry");
+        Some((stored_key, &mut entry.value))
+    }
+
+    /// Returns mutable references to the values corresponding to all `keys`.
+    ///
+    /// Returns `None` if any of the keys is missing or if a key is requested
+    /// more than once since this would hand out aliasing references.
+    ///
+    /// # Note
+    ///
+    /// The key may be any borrowed form of the map's key type,
+    /// but `Hash` and `Eq` on the borrowed form must match those for the key type.
+    pub fn get_many_mut<Q, const N: usize>(
+        &mut self,
+        keys: [&Q; N],
+    ) -> Option<[&mut V; N]>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        self.values
+            .get_many_mut(keys)
+            .map(|entries| entries.map(|entry| &mut entry.value))
+    }
+
     /// Returns `true` if there is an entry corresponding to the key in the map.
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
@@ -352,6 +880,153 @@ where
         self.values.get(key).is_some()
     }
 
+    /// Returns the keys of all `candidates` that are present in the map.
+    ///
+    /// The order of the returned keys follows the order of the candidates.
+    /// Candidates that are not in the map are skipped.
+    pub fn existing_keys<'a, Q, I>(&self, candidates: I) -> Vec<K>
+    where
+        K: Borrow<Q>,
+        Q: 'a + Ord + scale::Encode + ToOwned<Owned = K>,
+        I: IntoIterator<Item = &'a Q>,
+    {
+        candidates
+            .into_iter()
+            .filter_map(|candidate| self.values.get(candidate))
+            .map(|entry| {
+                self.keys
+                    .get(entry.key_index)
+                    .expect("`key_index` must point to a valid key entry")
+                    .clone()
+            })
+            .collect()
+    }
+
+    /// Moves all entries of `other` into `self`.
+    ///
+    /// For keys present in both maps `resolve(key, self_value, other_value)`
+    /// is called and its result stored under the key.
+    /// `other` is left empty and consumed.
+    pub fn merge_with<F>(&mut self, mut other: Self, mut resolve: F)
+    where
+        F: FnMut(&K, V, V) -> V,
+    {
+        let keys = other.keys().cloned().collect::<Vec<_>>();
+        for key in keys {
+            let other_value = other
+                .take(&key)
+                .expect("a key of the map must have a value");
+            let value = match self.take(&key) {
+                Some(self_value) => resolve(&key, self_value, other_value),
+                None => other_value,
+            };
+            self.insert(key, value);
+        }
+    }
+
+    /// Moves all entries of `other` into `self`.
+    ///
+    /// For keys present in both maps `on_conflict(key, self_value, other_value)`
+    /// is called to update the value of `self` in place. Other keys are inserted.
+    ///
+    /// # Note
+    ///
+    /// In contrast to [`HashMap::merge_with`] keys present in both maps keep
+    /// their key indices in `self`. `other` is drained and consumed.
+    pub fn merge<F>(&mut self, mut other: Self, mut on_conflict: F)
+    where
+        F: FnMut(&K, &mut V, V),
+    {
+        for (key, value) in other.drain() {
+            if let Some(self_value) = self.get_mut(&key) {
+                on_conflict(&key, self_value, value);
+            } else {
+                self.insert(key, value);
+            }
+        }
+    }
+
+    /// Retains only the entries for which `f` returns `true`.
+    ///
+    /// Returns the number of removed entries.
+    ///
+    /// # Note
+    ///
+    /// The removed entries leave vacant slots in the storage of the keys
+    /// just like [`HashMap::take`] does which can be freed by [`HashMap::defrag`].
+    pub fn retain<F>(&mut self, mut f: F) -> u32
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.retain_fold(0, |removed, key, value| {
+            let retain = f(key, value);
+            if !retain {
+                *removed += 1;
+            }
+            retain
+        })
+    }
+
+    /// Retains only the entries for which `f` returns `true` and compacts the
+    /// storage of the keys in the same pass.
+    ///
+    /// Returns the number of removed entries.
+    ///
+    /// # Note
+    ///
+    /// In contrast to [`HashMap::retain`] the retained keys are moved to the
+    /// lowest key indices as they are visited, so no vacant slots are left
+    /// behind and no subsequent [`HashMap::defrag`] is required.
+    pub fn retain_compact<F>(&mut self, mut f: F) -> u32
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let values = &mut self.values;
+        self.keys.retain_compact(|new_index, key| {
+            let entry = values
+                .get_mut(key)
+                .expect("a key in the stash must have a value");
+            let retain = f(key, &mut entry.value);
+            if retain {
+                entry.key_index = new_index;
+            } else {
+                values.put(key.clone(), None);
+            }
+            retain
+        })
+    }
+
+    /// Retains only the entries for which `f` returns `true` while folding
+    /// over all entries of the map.
+    ///
+    /// Returns the final value of the accumulator which starts as `init`.
+    ///
+    /// # Note
+    ///
+    /// The accumulator is available for every entry before its retention
+    /// is decided, so e.g. the values of removed entries can be summed up in
+    /// the same pass that removes them.
+    pub fn retain_fold<B, F>(&mut self, init: B, mut f: F) -> B
+    where
+        F: FnMut(&mut B, &K, &mut V) -> bool,
+    {
+        let mut acc = init;
+        let mut removed = Vec::new();
+        for key in self.keys.iter() {
+            let entry = self
+                .values
+                .get_mut(key)
+                .expect("a key in the stash must have a value");
+            if !f(&mut acc, key, &mut entry.value) {
+                removed.push(key.clone());
+            }
+        }
+        for key in &removed {
+            self.take(key);
+        }
+        acc
+    }
+
     /// Defragments storage used by the storage hash map.
     ///
     /// Returns the number of storage cells freed this way.
@@ -387,6 +1062,97 @@ where
         self.keys.defrag(Some(max_iterations), callback)
     }
 
+    /// Removes all key/value pairs from the hash map.
+    ///
+    /// # Note
+    ///
+    /// The values are cleared from the contract storage without being loaded.
+    /// Afterwards the storage held by the keys is freed so that keys inserted
+    /// later on are assigned fresh key indices starting at zero.
+    pub fn clear(&mut self) {
+        self.clear_cells();
+        for index in 0..self.keys.capacity() {
+            if let Some(key) = self.keys.take(index) {
+                // Marks the value as removed in the cache so that it is not
+                // written back to the contract storage upon the next push.
+                self.values.put(key, None);
+            }
+        }
+        self.defrag(None);
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key,
+    /// inserting the default value first if the key is not yet in the map.
+    ///
+    /// # Note
+    ///
+    /// This is a shorthand for `entry(key).or_default()` that only requires
+    /// `V: Default` and yields mutable access to the value.
+    /// The key is looked up in the contract storage only once.
+    pub fn get_or_insert_default(&mut self, key: K) -> &mut V
+    where
+        V: Default,
+    {
+        self.get_or_insert_with(key, V::default)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key,
+    /// inserting the value computed by `f` first if the key is not yet in the map.
+    ///
+    /// # Note
+    ///
+    /// The key is looked up in the contract storage only once and `f` is only
+    /// called if the key is vacant.
+    pub fn get_or_insert_with<F>(&mut self, key: K, f: F) -> &mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self.values.entry(key) {
+            LazyEntry::Occupied(occupied) => &mut occupied.into_mut().value,
+            LazyEntry::Vacant(vacant) => {
+                VacantEntry {
+                    keys: &mut self.keys,
+                    values_entry: vacant,
+                }
+                .insert(f())
+            }
+        }
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key,
+    /// inserting `V::default()` first if the key is not yet in the map.
+    ///
+    /// # Note
+    ///
+    /// Meant for accumulators such as `*map.entry_or_zero(key) += amount`.
+    /// In contrast to `entry(key).or_insert(0)` the default value is only
+    /// constructed if the key is vacant and no closure is involved.
+    /// The key is looked up in the contract storage only once.
+    pub fn entry_or_zero(&mut self, key: K) -> &mut V
+    where
+        V: Default,
+    {
+        self.get_or_insert_default(key)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key,
+    /// inserting the value computed by `f` first if the key is not yet in the map.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `f` without modifying the map if it fails.
+    ///
+    /// # Note
+    ///
+    /// This is a shorthand for `entry(key).or_try_insert_with_key(|_| f())`
+    /// that composes with the `?` operator in fallible messages.
+    pub fn get_or_try_insert<F, E>(&mut self, key: K, f: F) -> Result<&mut V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        self.entry(key).or_try_insert_with_key(|_| f())
+    }
+
     /// Gets the given key's corresponding entry in the map for in-place manipulation.
     pub fn entry(&mut self, key: K) -> Entry<K, V> {
         let entry = self.values.entry(key);
@@ -407,10 +1173,52 @@ where
     }
 }
 
+impl<A, B, V, H> HashMap<(A, B), V, H>
+where
+    A: Ord + PackedLayout,
+    B: Ord + PackedLayout,
+    (A, B): Clone,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    /// Returns a shared reference to the value corresponding to the pair of keys.
+    ///
+    /// # Note
+    ///
+    /// Allows to look up pair keys from borrowed components, e.g. the
+    /// `(owner, spender)` of an allowance, without cloning them. The key is
+    /// hashed from the borrowed components, see [`LazyHashMap::get_pair`].
+    pub fn get_pair(&self, a: &A, b: &B) -> Option<&V> {
+        self.values.get_pair(a, b).map(|entry| &entry.value)
+    }
+
+    /// Returns `true` if there is an entry for the pair of keys.
+    pub fn contains_pair(&self, a: &A, b: &B) -> bool {
+        self.values.contains_pair(a, b)
+    }
+
+    /// Removes the entry for the pair of keys from the map.
+    ///
+    /// - Returns the removed value if any.
+    ///
+    /// # Note
+    ///
+    /// The removed key is not loaded from the contract storage.
+    pub fn take_pair(&mut self, a: &A, b: &B) -> Option<V> {
+        let entry = self.values.take_pair(a, b)?;
+        // SAFETY: The `key_index` of a value entry always refers to the
+        //         occupied key entry of its associated key.
+        unsafe { self.keys.remove_occupied(entry.key_index) }
+            .expect("`key_index` must point to a valid key entry");
+        Some(entry.value)
+    }
+}
+
 impl<'a, K, V> Entry<'a, K, V>
 where
     K: Ord + Clone + PackedLayout,
-    V: PackedLayout + core::fmt::Debug + core::cmp::Eq + Default,
+    V: PackedLayout,
 {
     /// Returns a reference to this entry's key.
     pub fn key(&self) -> &K {
@@ -422,7 +1230,10 @@ where
 
     /// Ensures a value is in the entry by inserting the default value if empty, and returns
     /// a reference to the value in the entry.
-    pub fn or_default(self) -> &'a V {
+    pub fn or_default(self) -> &'a V
+    where
+        V: Default,
+    {
         match self {
             Entry::Occupied(entry) => &mut entry.values_entry.into_mut().value,
             Entry::Vacant(entry) => entry.insert(V::default()),
@@ -450,6 +1261,19 @@ where
         }
     }
 
+    /// Ensures a value is in the entry by inserting the result of the default function if empty,
+    /// and returns a mutable reference to the value in the entry together with a flag that is
+    /// `true` if the value has been inserted and `false` if the entry was already occupied.
+    pub fn or_insert_with_tracked<F>(self, default: F) -> (&'a mut V, bool)
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => (&mut entry.values_entry.into_mut().value, false),
+            Entry::Vacant(entry) => (Entry::insert(default(), entry), true),
+        }
+    }
+
     /// Ensures a value is in the entry by inserting, if empty, the result of the default
     /// function, which takes the key as its argument, and returns a mutable reference to
     /// the value in the entry.
@@ -463,6 +1287,52 @@ where
         }
     }
 
+    /// Ensures a value is in the entry by inserting, if empty, the result of the default
+    /// function, which takes the key as its argument, and returns a shared reference to
+    /// the key stored in the map together with a mutable reference to the value.
+    pub fn or_insert_with_key_ref<F>(self, default: F) -> (&'a K, &'a mut V)
+    where
+        F: FnOnce(&K) -> V,
+    {
+        let (keys, key_index, value) = match self {
+            Entry::Occupied(OccupiedEntry { keys, values_entry }) => {
+                let key_index = values_entry.get().key_index;
+                (keys, key_index, &mut values_entry.into_mut().value)
+            }
+            Entry::Vacant(VacantEntry { keys, values_entry }) => {
+                let value = default(values_entry.key());
+                let key_index = keys.put(values_entry.key().to_owned());
+                let value_entry = values_entry.insert(ValueEntry { value, key_index });
+                (keys, key_index, &mut value_entry.value)
+            }
+        };
+        let keys: &'a Stash<K> = keys;
+        let key = keys
+            .get(key_index)
+            .expect("`key_index` must point to a valid key entry");
+        (key, value)
+    }
+
+    /// Ensures a value is in the entry by inserting, if empty, the result of the fallible
+    /// default function, which takes the key as its argument, and returns a mutable
+    /// reference to the value in the entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the default function, in which case nothing is inserted.
+    pub fn or_try_insert_with_key<F, E>(self, default: F) -> Result<&'a mut V, E>
+    where
+        F: FnOnce(&K) -> Result<V, E>,
+    {
+        match self {
+            Entry::Occupied(entry) => Ok(&mut entry.values_entry.into_mut().value),
+            Entry::Vacant(entry) => {
+                let value = default(entry.key())?;
+                Ok(Entry::insert(value, entry))
+            }
+        }
+    }
+
     /// Provides in-place mutable access to an occupied entry before any
     /// potential inserts into the map.
     #[must_use]
@@ -482,6 +1352,34 @@ where
         }
     }
 
+    /// Provides fallible in-place mutable access to an occupied entry before any
+    /// potential inserts into the map.
+    ///
+    /// The closure operates on a copy of the value which is only written back into
+    /// the entry if it succeeds. Vacant entries are returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the closure, in which case the value is left unchanged.
+    pub fn and_try_modify<F, E>(self, f: F) -> Result<Self, E>
+    where
+        V: Clone,
+        F: FnOnce(&mut V) -> Result<(), E>,
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                {
+                    let v = entry.values_entry.get_mut();
+                    let mut value = v.value.clone();
+                    f(&mut value)?;
+                    v.value = value;
+                }
+                Ok(Entry::Occupied(entry))
+            }
+            Entry::Vacant(entry) => Ok(Entry::Vacant(entry)),
+        }
+    }
+
     /// Inserts `value` into `entry`.
     fn insert(value: V, entry: VacantEntry<'a, K, V>) -> &'a mut V {
         entry.insert(value)
@@ -503,6 +1401,12 @@ where
         self.values_entry.into_key()
     }
 
+    /// Returns the index in the key storage that the key would be stored at
+    /// when inserting a value through the `VacantEntry`.
+    pub fn would_use_index(&self) -> u32 {
+        self.keys.next_put_index()
+    }
+
     /// Sets the value of the entry with the `VacantEntry`s key, and returns a mutable reference to it.
     pub fn insert(self, value: V) -> &'a mut V {
         // At this point we know that `key` does not yet exist in the map.
@@ -534,6 +1438,11 @@ where
         (k, v.value)
     }
 
+    /// Returns the index in the key storage that the entry's key is stored at.
+    pub fn key_index(&self) -> u32 {
+        self.values_entry.get().key_index
+    }
+
     /// Gets a reference to the value in the entry.
     pub fn get(&self) -> &V {
         &self.values_entry.get().value
@@ -12,14 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::HashMap as StorageHashMap;
+use super::{
+    BoundExceeded,
+    DecodeError,
+    HashMap as StorageHashMap,
+    OccupiedError,
+};
 use crate::{
     traits::{
+        forward_clear_packed,
+        forward_pull_packed,
+        forward_push_packed,
         KeyPtr,
+        PackedLayout,
         SpreadLayout,
     },
     Lazy,
 };
+use ink_env::hash::{
+    CryptoHash,
+    HashOutput,
+};
 use ink_primitives::Key;
 
 /// Returns always the same `KeyPtr`.
@@ -87,6 +100,174 @@ fn from_empty_iterator_works() {
     );
 }
 
+#[test]
+fn from_iterator_with_duplicate_keys_works() {
+    let pairs = vec![(b'A', 1), (b'B', 2), (b'A', 10), (b'C', 3), (b'B', 20)];
+    let hmap = pairs.into_iter().collect::<StorageHashMap<u8, i32>>();
+    // Later duplicates overwrite the values of earlier ones.
+    assert_eq!(hmap.len(), 3);
+    assert_eq!(hmap.get(&b'A'), Some(&10));
+    assert_eq!(hmap.get(&b'B'), Some(&20));
+    assert_eq!(hmap.get(&b'C'), Some(&3));
+}
+
+#[test]
+fn extend_works() {
+    let mut hmap = filled_hmap();
+    hmap.extend(vec![(b'E', 5), (b'A', 10), (b'E', 50)]);
+    assert_eq!(hmap.len(), 5);
+    assert_eq!(hmap.get(&b'A'), Some(&10));
+    assert_eq!(hmap.get(&b'E'), Some(&50));
+    // Extending with an empty iterator changes nothing.
+    hmap.extend(Vec::new());
+    assert_eq!(hmap.len(), 5);
+    assert_eq!(
+        hmap,
+        [(b'A', 10), (b'B', 2), (b'C', 3), (b'D', 4), (b'E', 50)]
+            .iter()
+            .copied()
+            .collect::<StorageHashMap<u8, i32>>()
+    );
+}
+
+#[test]
+fn get_or_works() {
+    let hmap = filled_hmap();
+    let default = 0;
+    // Present keys yield the stored value.
+    assert!(core::ptr::eq(
+        hmap.get_or(&b'A', &default),
+        hmap.get(&b'A').unwrap()
+    ));
+    assert_eq!(hmap.get_or(&b'D', &default), &4);
+    // Absent keys yield the provided default without inserting it.
+    assert!(core::ptr::eq(hmap.get_or(&b'E', &default), &default));
+    assert_eq!(hmap.get(&b'E'), None);
+    assert_eq!(hmap.len(), 4);
+}
+
+#[test]
+fn capacity_and_reserve_work() {
+    let mut hmap = <StorageHashMap<u8, i32>>::new();
+    assert_eq!(hmap.capacity(), 0);
+    hmap.reserve(3);
+    assert_eq!(hmap.capacity(), 3);
+    assert_eq!(hmap.len(), 0);
+    // Inserting into the reserved capacity does not grow it.
+    hmap.extend(vec![(b'A', 1), (b'B', 2), (b'C', 3)]);
+    assert_eq!(hmap.capacity(), 3);
+    assert_eq!(hmap.len(), 3);
+    // Inserting beyond the capacity grows it.
+    hmap.insert(b'D', 4);
+    assert_eq!(hmap.capacity(), 4);
+    hmap.reserve(2);
+    assert_eq!(hmap.capacity(), 6);
+    assert_eq!(hmap, filled_hmap());
+    // Unused reserved capacity is freed by defragmentation.
+    assert_eq!(hmap.defrag(None), 2);
+    assert_eq!(hmap.capacity(), 4);
+    assert_eq!(hmap, filled_hmap());
+}
+
+#[test]
+fn shrink_to_works() {
+    let mut hmap = holey_hmap();
+    hmap.reserve(7);
+    assert_eq!(hmap.capacity(), 12);
+    let contents = |hmap: &StorageHashMap<u8, i32>| {
+        hmap.iter()
+            .map(|(k, v)| (*k, *v))
+            .collect::<std::collections::BTreeMap<_, _>>()
+    };
+    let expected = contents(&hmap);
+    // Shrinking to a target above the length keeps a reserve.
+    hmap.shrink_to(7);
+    assert_eq!(hmap.capacity(), 7);
+    assert_eq!(contents(&hmap), expected);
+    // Shrinking never grows the capacity.
+    hmap.shrink_to(10);
+    assert_eq!(hmap.capacity(), 7);
+    // Shrinking below the length stops at the length.
+    hmap.shrink_to(0);
+    assert_eq!(hmap.capacity(), hmap.len());
+    assert_eq!(hmap.len(), 5);
+    assert_eq!(contents(&hmap), expected);
+    for (key, value) in &expected {
+        assert_eq!(hmap.get(key), Some(value));
+    }
+    // The map stays usable afterwards.
+    assert_eq!(hmap.insert(b'Z', 26), None);
+    assert_eq!(hmap.capacity(), 6);
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn shrink_to_clears_freed_storage() -> ink_env::Result<()> {
+    use ink_env::DefaultEnvironment;
+    ink_env::test::run_test::<DefaultEnvironment, _>(|_| {
+        let contract = ink_env::test::get_current_contract_account_id::<
+            DefaultEnvironment,
+        >()?;
+        let used_cells =
+            || ink_env::test::count_used_storage_cells::<DefaultEnvironment>(&contract);
+        let mut hmap = filled_hmap();
+        hmap.reserve(4);
+        push_hmap(&hmap);
+        let used_cells_reserved = used_cells()?;
+
+        let mut hmap = pull_hmap();
+        assert_eq!(hmap.capacity(), 8);
+        hmap.shrink_to(6);
+        push_hmap(&hmap);
+        assert_eq!(used_cells()?, used_cells_reserved - 2);
+        let pulled = pull_hmap();
+        assert_eq!(pulled.capacity(), 6);
+        assert_eq!(pulled, filled_hmap());
+        Ok(())
+    })
+}
+
+#[test]
+fn sum_by_works() {
+    // Each value is a `(balance, nonce)` pair.
+    let hmap = [(b'A', (10, 1)), (b'B', (20, 2)), (b'C', (30, 3))]
+        .iter()
+        .copied()
+        .collect::<StorageHashMap<u8, (u128, u32)>>();
+    let mut total = 0;
+    for (_, (balance, _)) in hmap.iter() {
+        total += balance;
+    }
+    assert_eq!(hmap.sum_by(|(balance, _)| *balance), total);
+    assert_eq!(hmap.sum_by(|(_, nonce)| u64::from(*nonce)), 6);
+    assert_eq!(<StorageHashMap<u8, i32>>::new().sum_by(|value| *value), 0);
+}
+
+#[test]
+fn iter_follows_stash_index_order() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        let mut hmap = filled_hmap();
+        // Vacated key indices are refilled before the stash grows.
+        assert_eq!(hmap.take(&b'B'), Some(2));
+        hmap.insert(b'E', 5);
+        assert_eq!(hmap.take(&b'D'), Some(4));
+        assert_eq!(hmap.take(&b'A'), Some(1));
+        hmap.insert(b'F', 6);
+        hmap.insert(b'G', 7);
+        hmap.insert(b'H', 8);
+        // Stash indices: F = 0, E = 1, C = 2, G = 3, H = 4.
+        let expected = vec![(b'F', 6), (b'E', 5), (b'C', 3), (b'G', 7), (b'H', 8)];
+        let pairs = |hmap: &StorageHashMap<u8, i32>| {
+            hmap.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>()
+        };
+        assert_eq!(pairs(&hmap), expected);
+        // The order is the same after loading the hash map from storage.
+        push_hmap(&hmap);
+        assert_eq!(pairs(&pull_hmap()), expected);
+        Ok(())
+    })
+}
+
 #[test]
 fn contains_key_works() {
     // Empty hash map.
@@ -127,6 +308,32 @@ fn get_works() {
     assert_eq!(hmap.get_mut(&b'E'), None);
 }
 
+#[test]
+fn index_works() {
+    let mut hmap = filled_hmap();
+    assert_eq!(hmap[&b'A'], 1);
+    assert_eq!(hmap[&b'B'], 2);
+    assert_eq!(hmap[&b'C'], 3);
+    assert_eq!(hmap[&b'D'], 4);
+    hmap[&b'A'] += 10;
+    assert_eq!(hmap[&b'A'], 11);
+    assert_eq!(hmap.get(&b'A'), Some(&11));
+}
+
+#[test]
+#[should_panic(expected = "no entry found for key")]
+fn index_absent_key_fails() {
+    let hmap = filled_hmap();
+    let _ = hmap[&b'E'];
+}
+
+#[test]
+#[should_panic(expected = "no entry found for key")]
+fn index_mut_absent_key_fails() {
+    let mut hmap = filled_hmap();
+    hmap[&b'E'] = 5;
+}
+
 #[test]
 fn insert_works() {
     let mut hmap = <StorageHashMap<u8, i32>>::new();
@@ -289,164 +496,1811 @@ fn defrag_works() {
 }
 
 #[test]
-fn spread_layout_push_pull_works() -> ink_env::Result<()> {
+fn take_and_defrag_works() {
+    let mut hmap = filled_hmap();
+    assert_eq!(hmap.keys.capacity(), 4);
+    assert_eq!(hmap.take_and_defrag(&b'B'), Some(2));
+    assert_eq!(hmap.keys.capacity(), 3);
+    assert_eq!(hmap.take_and_defrag(&b'B'), None);
+    assert_eq!(hmap.take_and_defrag(&b'A'), Some(1));
+    assert_eq!(hmap.keys.capacity(), 2);
+    assert_eq!(hmap.len(), 2);
+    // Storage stays compact when entries are inserted and removed repeatedly.
+    for n in 0..10 {
+        hmap.insert(b'X', n);
+        assert_eq!(hmap.take_and_defrag(&b'C'), Some(3));
+        assert_eq!(hmap.keys.capacity(), hmap.len());
+        hmap.insert(b'C', 3);
+        assert_eq!(hmap.take_and_defrag(&b'X'), Some(n));
+        assert_eq!(hmap.keys.capacity(), hmap.len());
+    }
+    assert_eq!(hmap.get(&b'C'), Some(&3));
+    assert_eq!(hmap.get(&b'D'), Some(&4));
+}
+
+#[test]
+fn entry_key_indices_work() {
+    use super::Entry;
+    let mut hmap = filled_hmap();
+    // Existing keys report the index they have been inserted at.
+    for (n, key) in [b'A', b'B', b'C', b'D'].iter().enumerate() {
+        match hmap.entry(*key) {
+            Entry::Occupied(occupied) => assert_eq!(occupied.key_index(), n as u32),
+            Entry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+    }
+    // New keys are appended to the key storage ...
+    let expected = match hmap.entry(b'E') {
+        Entry::Vacant(vacant) => {
+            let index = vacant.would_use_index();
+            vacant.insert(5);
+            index
+        }
+        Entry::Occupied(_) => panic!("expected a vacant entry"),
+    };
+    assert_eq!(expected, 4);
+    assert_eq!(hmap.keys.get(expected), Some(&b'E'));
+    // ... unless there are vacant slots that can be reused.
+    assert_eq!(hmap.take(&b'B'), Some(2));
+    let expected = match hmap.entry(b'F') {
+        Entry::Vacant(vacant) => {
+            let index = vacant.would_use_index();
+            vacant.insert(6);
+            index
+        }
+        Entry::Occupied(_) => panic!("expected a vacant entry"),
+    };
+    assert_eq!(expected, 1);
+    assert_eq!(hmap.keys.get(expected), Some(&b'F'));
+    match hmap.entry(b'F') {
+        Entry::Occupied(occupied) => assert_eq!(occupied.key_index(), expected),
+        Entry::Vacant(_) => panic!("expected an occupied entry"),
+    }
+}
+
+#[test]
+fn or_insert_with_tracked_works() {
+    let mut hmap = filled_hmap();
+    // Vacant entry: the value is inserted.
+    let (value, inserted) = hmap.entry(b'E').or_insert_with_tracked(|| 5);
+    assert_eq!(*value, 5);
+    assert!(inserted);
+    *value += 1;
+    assert_eq!(hmap.get(&b'E'), Some(&6));
+    assert_eq!(hmap.len(), 5);
+    // Occupied entry: the existing value is returned and the default is not called.
+    let (value, inserted) = hmap
+        .entry(b'A')
+        .or_insert_with_tracked(|| panic!("must not be called for occupied entries"));
+    assert_eq!(*value, 1);
+    assert!(!inserted);
+    assert_eq!(hmap.len(), 5);
+}
+
+#[test]
+fn or_try_insert_with_key_works() {
+    let mut hmap = filled_hmap();
+    // Vacant entry: the value is derived from the key and inserted.
+    let value = hmap
+        .entry(b'E')
+        .or_try_insert_with_key(|key| Ok::<_, ()>(i32::from(*key)));
+    assert_eq!(value, Ok(&mut i32::from(b'E')));
+    assert_eq!(hmap.len(), 5);
+    // Vacant entry: a failing default leaves the map unchanged.
+    assert_eq!(
+        hmap.entry(b'F').or_try_insert_with_key(|key| Err(*key)),
+        Err(b'F')
+    );
+    assert_eq!(hmap.get(&b'F'), None);
+    assert_eq!(hmap.len(), 5);
+    // Occupied entry: the existing value is returned and the default is not called.
+    let value = hmap
+        .entry(b'A')
+        .or_try_insert_with_key(|_| -> Result<i32, ()> { panic!("must not be called") });
+    assert_eq!(value, Ok(&mut 1));
+    assert_eq!(hmap.len(), 5);
+}
+
+#[test]
+fn get_or_try_insert_works() {
+    let mut hmap = filled_hmap();
+    // Vacant key: the computed value is inserted.
+    assert_eq!(hmap.get_or_try_insert(b'E', || Ok::<_, ()>(5)), Ok(&mut 5));
+    assert_eq!(hmap.get(&b'E'), Some(&5));
+    assert_eq!(hmap.len(), 5);
+    // Vacant key: a failing computation leaves the map unchanged.
+    assert_eq!(hmap.get_or_try_insert(b'F', || Err("failed")), Err("failed"));
+    assert_eq!(hmap.get(&b'F'), None);
+    assert_eq!(hmap.len(), 5);
+    // Occupied key: the existing value is returned and `f` is not called.
+    let value = hmap.get_or_try_insert(b'A', || -> Result<i32, ()> {
+        panic!("must not be called")
+    });
+    assert_eq!(value, Ok(&mut 1));
+    *value.unwrap() += 10;
+    assert_eq!(hmap.get(&b'A'), Some(&11));
+}
+
+#[test]
+fn collect_keys_bounded_works() {
+    let hmap = filled_hmap();
+    assert_eq!(
+        hmap.collect_keys_bounded(4),
+        Ok(vec![b'A', b'B', b'C', b'D'])
+    );
+    assert_eq!(
+        hmap.collect_keys_bounded(10),
+        Ok(vec![b'A', b'B', b'C', b'D'])
+    );
+    assert_eq!(
+        <StorageHashMap<u8, i32>>::new().collect_keys_bounded(0),
+        Ok(vec![])
+    );
+}
+
+#[test]
+fn collect_keys_bounded_fails_for_big_maps() {
+    let hmap = filled_hmap();
+    assert_eq!(hmap.collect_keys_bounded(3), Err(BoundExceeded { len: 4 }));
+    assert_eq!(hmap.collect_keys_bounded(0), Err(BoundExceeded { len: 4 }));
+}
+
+#[test]
+fn get_or_insert_default_works() {
+    let mut hmap = filled_hmap();
+    // Absent keys are inserted with the default value.
+    assert_eq!(hmap.get_or_insert_default(b'E'), &mut 0);
+    assert_eq!(hmap.len(), 5);
+    assert_eq!(hmap.get(&b'E'), Some(&0));
+    // Present keys return the existing value.
+    *hmap.get_or_insert_default(b'A') += 10;
+    *hmap.get_or_insert_default(b'A') += 10;
+    assert_eq!(hmap.get(&b'A'), Some(&21));
+    assert_eq!(hmap.len(), 5);
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn get_or_insert_default_resolves_once() -> ink_env::Result<()> {
     ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
-        let hmap1 = filled_hmap();
-        push_hmap(&hmap1);
-        // Load the pushed storage hmap into another instance and check that
-        // both instances are equal:
-        let hmap2 = pull_hmap();
-        assert_eq!(hmap1, hmap2);
+        push_hmap(&filled_hmap());
+        let mut hmap = pull_hmap();
+        let contract_id = ink_env::test::get_current_contract_account_id::<
+            ink_env::DefaultEnvironment,
+        >()?;
+        let (base_reads, _) = ink_env::test::get_contract_storage_rw::<
+            ink_env::DefaultEnvironment,
+        >(&contract_id)?;
+
+        *hmap.get_or_insert_default(b'B') += 1;
+        *hmap.get_or_insert_default(b'B') += 1;
+
+        let (reads, _) = ink_env::test::get_contract_storage_rw::<
+            ink_env::DefaultEnvironment,
+        >(&contract_id)?;
+        assert_eq!(reads - base_reads, 1);
+        assert_eq!(hmap.get(&b'B'), Some(&4));
         Ok(())
     })
 }
 
 #[test]
-#[should_panic(expected = "storage entry was empty")]
-fn spread_layout_clear_works() {
+#[cfg(feature = "ink-experimental-engine")]
+fn get_or_insert_default_resolves_once() -> ink_env::Result<()> {
     ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
-        let hmap1 = filled_hmap();
-        let root_key = Key::from([0x42; 32]);
-        SpreadLayout::push_spread(&hmap1, &mut KeyPtr::from(root_key));
-        // It has already been asserted that a valid instance can be pulled
-        // from contract storage after a push to the same storage region.
-        //
-        // Now clear the associated storage from `hmap1` and check whether
-        // loading another instance from this storage will panic since the
-        // hmap's length property cannot read a value:
-        SpreadLayout::clear_spread(&hmap1, &mut KeyPtr::from(root_key));
-        let _ = <StorageHashMap<u8, i32> as SpreadLayout>::pull_spread(
-            &mut KeyPtr::from(root_key),
-        );
+        push_hmap(&filled_hmap());
+        let mut hmap = pull_hmap();
+        let contract_id = ink_env::test::callee::<ink_env::DefaultEnvironment>();
+        let (base_reads, _) = ink_env::test::get_contract_storage_rw::<
+            ink_env::DefaultEnvironment,
+        >(&contract_id);
+
+        *hmap.get_or_insert_default(b'B') += 1;
+        *hmap.get_or_insert_default(b'B') += 1;
+
+        let (reads, _) = ink_env::test::get_contract_storage_rw::<
+            ink_env::DefaultEnvironment,
+        >(&contract_id);
+        assert_eq!(reads - base_reads, 1);
+        assert_eq!(hmap.get(&b'B'), Some(&4));
         Ok(())
     })
-    .unwrap()
+}
+
+#[test]
+fn get_or_insert_with_works() {
+    let mut hmap = filled_hmap();
+    // Absent keys are inserted with the computed value.
+    assert_eq!(hmap.get_or_insert_with(b'E', || 5), &mut 5);
+    assert_eq!(hmap.len(), 5);
+    assert_eq!(hmap.get(&b'E'), Some(&5));
+    // Present keys return the existing value without calling the closure.
+    let value = hmap.get_or_insert_with(b'A', || panic!("must not be called"));
+    *value += 10;
+    assert_eq!(hmap.get(&b'A'), Some(&11));
+    assert_eq!(hmap.len(), 5);
 }
 
 #[test]
 #[cfg(not(feature = "ink-experimental-engine"))]
-fn storage_is_cleared_completely_after_pull_lazy() {
+fn get_or_insert_with_reads_once() -> ink_env::Result<()> {
     ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
-        // given
-        let root_key = Key::from([0x42; 32]);
-        let lazy_hmap = Lazy::new(filled_hmap());
-        SpreadLayout::push_spread(&lazy_hmap, &mut KeyPtr::from(root_key));
-        let pulled_hmap = <Lazy<StorageHashMap<u8, i32>> as SpreadLayout>::pull_spread(
-            &mut KeyPtr::from(root_key),
-        );
-
-        // when
-        SpreadLayout::clear_spread(&pulled_hmap, &mut KeyPtr::from(root_key));
-
-        // then
+        push_hmap(&filled_hmap());
+        let mut hmap = pull_hmap();
         let contract_id = ink_env::test::get_current_contract_account_id::<
             ink_env::DefaultEnvironment,
-        >()
-        .expect("Cannot get contract id");
-        let used_cells = ink_env::test::count_used_storage_cells::<
-            ink_env::DefaultEnvironment,
-        >(&contract_id)
-        .expect("used cells must be returned");
-        assert_eq!(used_cells, 0);
-
+        >()?;
+        let reads = || -> ink_env::Result<usize> {
+            let (reads, _) = ink_env::test::get_contract_storage_rw::<
+                ink_env::DefaultEnvironment,
+            >(&contract_id)?;
+            Ok(reads)
+        };
+        // A hit performs a single lookup of the value.
+        let base_reads = reads()?;
+        *hmap.get_or_insert_with(b'C', || 0) += 1;
+        assert_eq!(reads()? - base_reads, 1);
+        // A miss performs a single lookup of the value as well.
+        let base_reads = reads()?;
+        *hmap.get_or_insert_with(b'E', || 0) += 1;
+        assert_eq!(reads()? - base_reads, 1);
+        assert_eq!(hmap.get(&b'C'), Some(&4));
+        assert_eq!(hmap.get(&b'E'), Some(&1));
         Ok(())
     })
-    .unwrap()
 }
 
 #[test]
-#[should_panic(expected = "storage entry was empty")]
 #[cfg(not(feature = "ink-experimental-engine"))]
-fn drop_works() {
+fn entry_and_modify_reads_once() -> ink_env::Result<()> {
+    use super::Entry;
     ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
-        let root_key = Key::from([0x42; 32]);
+        push_hmap(&filled_hmap());
+        let mut hmap = pull_hmap();
+        let contract_id = ink_env::test::get_current_contract_account_id::<
+            ink_env::DefaultEnvironment,
+        >()?;
+        let reads = || -> ink_env::Result<usize> {
+            let (reads, _) = ink_env::test::get_contract_storage_rw::<
+                ink_env::DefaultEnvironment,
+            >(&contract_id)?;
+            Ok(reads)
+        };
+        // The occupied entry holds on to the value it has resolved.
+        let base_reads = reads()?;
+        hmap.entry(b'B').and_modify(|value| *value += 10).or_insert(0);
+        assert_eq!(reads()? - base_reads, 1);
 
-        // if the setup panics it should not cause the test to pass
-        let setup_result = std::panic::catch_unwind(|| {
-            let hmap = filled_hmap();
-            SpreadLayout::push_spread(&hmap, &mut KeyPtr::from(root_key));
-            let _ = <StorageHashMap<u8, i32> as SpreadLayout>::pull_spread(
-                &mut KeyPtr::from(root_key),
-            );
-            // hmap is dropped which should clear the cells
-        });
-        assert!(setup_result.is_ok(), "setup should not panic");
+        let base_reads = reads()?;
+        match hmap.entry(b'C') {
+            Entry::Occupied(mut occupied) => {
+                *occupied.get_mut() += 10;
+                assert_eq!(occupied.get(), &13);
+            }
+            Entry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+        assert_eq!(reads()? - base_reads, 1);
+        assert_eq!(hmap.get(&b'B'), Some(&12));
+        Ok(())
+    })
+}
 
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn key_set_reads_no_values() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        push_hmap(&filled_hmap());
+        let hmap = pull_hmap();
         let contract_id = ink_env::test::get_current_contract_account_id::<
             ink_env::DefaultEnvironment,
-        >()
-        .expect("Cannot get contract id");
-        let used_cells = ink_env::test::count_used_storage_cells::<
-            ink_env::DefaultEnvironment,
-        >(&contract_id)
-        .expect("used cells must be returned");
-        assert_eq!(used_cells, 0);
+        >()?;
+        let reads = || -> ink_env::Result<usize> {
+            let (reads, _) = ink_env::test::get_contract_storage_rw::<
+                ink_env::DefaultEnvironment,
+            >(&contract_id)?;
+            Ok(reads)
+        };
+        let base_reads = reads()?;
+        let keys = hmap.key_set().copied().collect::<Vec<_>>();
+        assert_eq!(keys, vec![b'A', b'B', b'C', b'D']);
+        // Only a single cell per key has been read.
+        assert_eq!(reads()? - base_reads, keys.len());
 
-        let _ = <StorageHashMap<u8, i32> as SpreadLayout>::pull_spread(
-            &mut KeyPtr::from(root_key),
-        );
+        // No value has been loaded, so every value is read now.
+        let base_reads = reads()?;
+        for key in &keys {
+            assert!(hmap.get(key).is_some());
+        }
+        assert_eq!(reads()? - base_reads, keys.len());
         Ok(())
     })
-    .unwrap()
 }
 
 #[test]
-#[cfg(feature = "ink-experimental-engine")]
-fn storage_is_cleared_completely_after_pull_lazy() {
+fn entry_or_zero_works() {
+    let mut hmap = filled_hmap();
+    *hmap.entry_or_zero(b'A') += 10;
+    *hmap.entry_or_zero(b'E') += 5;
+    *hmap.entry_or_zero(b'E') += 5;
+    assert_eq!(hmap.get(&b'A'), Some(&11));
+    assert_eq!(hmap.get(&b'E'), Some(&10));
+    assert_eq!(hmap.len(), 5);
+    // Only a vacant key inserts the default value.
+    assert_eq!(*hmap.entry_or_zero(b'F'), 0);
+    assert_eq!(hmap.len(), 6);
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn entry_or_zero_resolves_once() -> ink_env::Result<()> {
     ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
-        // given
-        let root_key = Key::from([0x42; 32]);
-        let lazy_hmap = Lazy::new(filled_hmap());
-        SpreadLayout::push_spread(&lazy_hmap, &mut KeyPtr::from(root_key));
-        let pulled_hmap = <Lazy<StorageHashMap<u8, i32>> as SpreadLayout>::pull_spread(
-            &mut KeyPtr::from(root_key),
-        );
+        push_hmap(&filled_hmap());
+        let mut hmap = pull_hmap();
+        let contract_id = ink_env::test::get_current_contract_account_id::<
+            ink_env::DefaultEnvironment,
+        >()?;
+        let (base_reads, _) = ink_env::test::get_contract_storage_rw::<
+            ink_env::DefaultEnvironment,
+        >(&contract_id)?;
 
-        // when
-        SpreadLayout::clear_spread(&pulled_hmap, &mut KeyPtr::from(root_key));
+        *hmap.entry_or_zero(b'C') += 1;
+        *hmap.entry_or_zero(b'C') += 1;
 
-        // then
-        let contract_id = ink_env::test::callee::<ink_env::DefaultEnvironment>();
-        let used_cells = ink_env::test::count_used_storage_cells::<
+        let (reads, _) = ink_env::test::get_contract_storage_rw::<
             ink_env::DefaultEnvironment,
-        >(&contract_id)
-        .expect("used cells must be returned");
-        assert_eq!(used_cells, 0);
-
+        >(&contract_id)?;
+        assert_eq!(reads - base_reads, 1);
+        assert_eq!(hmap.get(&b'C'), Some(&5));
         Ok(())
     })
-    .unwrap()
 }
 
 #[test]
-#[should_panic(expected = "storage entry was empty")]
 #[cfg(feature = "ink-experimental-engine")]
-fn drop_works() {
+fn entry_or_zero_resolves_once() -> ink_env::Result<()> {
     ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
-        let root_key = Key::from([0x42; 32]);
-
-        // if the setup panics it should not cause the test to pass
-        let setup_result = std::panic::catch_unwind(|| {
-            let hmap = filled_hmap();
-            SpreadLayout::push_spread(&hmap, &mut KeyPtr::from(root_key));
-            let _ = <StorageHashMap<u8, i32> as SpreadLayout>::pull_spread(
-                &mut KeyPtr::from(root_key),
-            );
-            // hmap is dropped which should clear the cells
-        });
-        assert!(setup_result.is_ok(), "setup should not panic");
-
+        push_hmap(&filled_hmap());
+        let mut hmap = pull_hmap();
         let contract_id = ink_env::test::callee::<ink_env::DefaultEnvironment>();
-        let used_cells = ink_env::test::count_used_storage_cells::<
+        let (base_reads, _) = ink_env::test::get_contract_storage_rw::<
             ink_env::DefaultEnvironment,
-        >(&contract_id)
-        .expect("used cells must be returned");
-        assert_eq!(used_cells, 0);
+        >(&contract_id);
 
-        let _ = <StorageHashMap<u8, i32> as SpreadLayout>::pull_spread(
-            &mut KeyPtr::from(root_key),
-        );
+        *hmap.entry_or_zero(b'C') += 1;
+        *hmap.entry_or_zero(b'C') += 1;
+
+        let (reads, _) = ink_env::test::get_contract_storage_rw::<
+            ink_env::DefaultEnvironment,
+        >(&contract_id);
+        assert_eq!(reads - base_reads, 1);
+        assert_eq!(hmap.get(&b'C'), Some(&5));
         Ok(())
     })
-    .unwrap()
+}
+
+#[test]
+fn get_key_value_mut_works() {
+    let mut hmap = filled_hmap();
+    assert_eq!(hmap.get_key_value_mut(&b'E'), None);
+    let (key, value) = hmap.get_key_value_mut(&b'B').unwrap();
+    assert_eq!(*key, b'B');
+    *value *= 10;
+    assert_eq!(hmap.get(&b'B'), Some(&20));
+    assert_eq!(hmap.get_key_value_mut(&b'B'), Some((&b'B', &mut 20)));
+    assert_eq!(hmap.len(), 4);
+}
+
+/// A key that is `==` to other keys with the same `id` regardless of its `tag`.
+///
+/// Only the `id` is encoded, so keys with different tags refer to the same cell.
+#[derive(Debug, Clone)]
+struct TaggedKey {
+    id: u8,
+    tag: u8,
+}
+
+impl PartialEq for TaggedKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for TaggedKey {}
+
+impl PartialOrd for TaggedKey {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TaggedKey {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl scale::Encode for TaggedKey {
+    fn encode_to<T: scale::Output + ?Sized>(&self, dest: &mut T) {
+        self.id.encode_to(dest)
+    }
+}
+
+impl scale::Decode for TaggedKey {
+    fn decode<I: scale::Input>(input: &mut I) -> Result<Self, scale::Error> {
+        Ok(Self {
+            id: u8::decode(input)?,
+            tag: 0,
+        })
+    }
+}
+
+impl SpreadLayout for TaggedKey {
+    const FOOTPRINT: u64 = 1;
+    const REQUIRES_DEEP_CLEAN_UP: bool = false;
+
+    fn pull_spread(ptr: &mut KeyPtr) -> Self {
+        forward_pull_packed::<Self>(ptr)
+    }
+
+    fn push_spread(&self, ptr: &mut KeyPtr) {
+        forward_push_packed::<Self>(self, ptr)
+    }
+
+    fn clear_spread(&self, ptr: &mut KeyPtr) {
+        forward_clear_packed::<Self>(self, ptr)
+    }
+}
+
+impl PackedLayout for TaggedKey {
+    fn pull_packed(&mut self, _at: &Key) {}
+    fn push_packed(&self, _at: &Key) {}
+    fn clear_packed(&self, _at: &Key) {}
+}
+
+#[test]
+fn get_key_value_works() {
+    let mut hmap = filled_hmap();
+    assert_eq!(hmap.get_key_value(&b'E'), None);
+    assert_eq!(hmap.get_key_value(&b'B'), Some((&b'B', &2)));
+    hmap.take(&b'A');
+    assert_eq!(hmap.get_key_value(&b'A'), None);
+    assert_eq!(hmap.get_key_value(&b'D'), Some((&b'D', &4)));
+}
+
+#[test]
+fn get_key_value_returns_stored_key() {
+    let mut hmap = <StorageHashMap<TaggedKey, i32>>::new();
+    hmap.insert(TaggedKey { id: 1, tag: 7 }, 10);
+    hmap.insert(TaggedKey { id: 2, tag: 8 }, 20);
+    let lookup = TaggedKey { id: 1, tag: 0 };
+    let (stored_key, value) = hmap.get_key_value(&lookup).unwrap();
+    assert_eq!(stored_key, &lookup);
+    assert_eq!((stored_key.id, stored_key.tag), (1, 7));
+    assert_eq!(*value, 10);
+    // Inserting an equal key updates the value but keeps the stored key.
+    hmap.insert(TaggedKey { id: 2, tag: 9 }, 21);
+    let (stored_key, value) = hmap.get_key_value(&TaggedKey { id: 2, tag: 0 }).unwrap();
+    assert_eq!((stored_key.tag, *value), (8, 21));
+}
+
+#[test]
+fn debug_cell_keys_works() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        // Not yet associated with a storage location.
+        assert_eq!(filled_hmap().debug_cell_keys(), vec![]);
+        push_hmap(&filled_hmap());
+        let hmap = pull_hmap();
+        let cell_keys = hmap.debug_cell_keys();
+        assert_eq!(
+            cell_keys.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+            vec![b'A', b'B', b'C', b'D']
+        );
+        // The values are stored behind the keys stash at `[0x42; 32] + 2^32 + 1`.
+        // Their cell keys are the BLAKE-2 256-bit hashes of the SCALE encoded
+        // `(b"ink hashmap", values_key, key)` tuples.
+        assert_eq!(
+            cell_keys[0].1,
+            Key::from(*b"\
+            \x26\xA5\xD5\x12\x3A\x5F\x68\x6A\
+            \x4B\xA5\x3D\x29\x50\xFD\x07\x60\
+            \xC2\x90\x79\x38\xBF\x66\x70\x35\
+            \x8B\xD9\x14\x7C\x79\xC2\x65\xC4")
+        );
+        assert_eq!(
+            cell_keys[1].1,
+            Key::from(*b"\
+            \x3C\xA8\x91\xA1\xCC\x10\xE1\x4E\
+            \xDD\x8F\x1C\xF0\x68\x9C\xCB\x96\
+            \x95\xBA\xF3\x88\x65\x57\xD9\x27\
+            \x7E\x2B\x21\x10\x26\x75\xED\xD2")
+        );
+        Ok(())
+    })
+}
+
+/// Asserts that a hash map using the hash function `H` round trips through the
+/// contract storage and stores its values in other cells than with BLAKE-2.
+fn assert_hasher_works<H>() -> ink_env::Result<()>
+where
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        push_hmap(&filled_hmap());
+        let blake2_cell_keys = pull_hmap().debug_cell_keys();
+        let hmap = filled_hmap()
+            .iter()
+            .map(|(key, value)| (*key, *value))
+            .collect::<StorageHashMap<u8, i32, H>>();
+        SpreadLayout::push_spread(&hmap, &mut key_ptr());
+        let pulled =
+            <StorageHashMap<u8, i32, H> as SpreadLayout>::pull_spread(&mut key_ptr());
+        assert_eq!(
+            pulled.iter().collect::<Vec<_>>(),
+            filled_hmap().iter().collect::<Vec<_>>()
+        );
+        for key in b"ABCD" {
+            assert_eq!(pulled.get(key), filled_hmap().get(key));
+        }
+        let cell_keys = pulled.debug_cell_keys();
+        assert_eq!(cell_keys.len(), blake2_cell_keys.len());
+        for ((key, cell), (blake2_key, blake2_cell)) in
+            cell_keys.iter().zip(&blake2_cell_keys)
+        {
+            assert_eq!(key, blake2_key);
+            assert_ne!(cell, blake2_cell);
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn keccak256_hash_map_works() -> ink_env::Result<()> {
+    assert_hasher_works::<ink_env::hash::Keccak256>()
+}
+
+#[test]
+fn sha2x256_hash_map_works() -> ink_env::Result<()> {
+    assert_hasher_works::<ink_env::hash::Sha2x256>()
+}
+
+#[test]
+fn different_hashers_yield_different_cell_keys() -> ink_env::Result<()> {
+    use ink_env::hash::Sha2x256;
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        // Both hash maps are stored at the same root key.
+        push_hmap(&filled_hmap());
+        let blake2_cell_keys = pull_hmap().debug_cell_keys();
+        let sha2_hmap = filled_hmap()
+            .iter()
+            .map(|(key, value)| (*key, *value))
+            .collect::<StorageHashMap<u8, i32, Sha2x256>>();
+        SpreadLayout::push_spread(&sha2_hmap, &mut key_ptr());
+        let sha2_cell_keys =
+            <StorageHashMap<u8, i32, Sha2x256> as SpreadLayout>::pull_spread(
+                &mut key_ptr(),
+            )
+            .debug_cell_keys();
+        assert_eq!(blake2_cell_keys.len(), 4);
+        assert_eq!(sha2_cell_keys.len(), 4);
+        for ((blake2_key, blake2_cell), (sha2_key, sha2_cell)) in
+            blake2_cell_keys.iter().zip(&sha2_cell_keys)
+        {
+            assert_eq!(blake2_key, sha2_key);
+            assert_ne!(blake2_cell, sha2_cell);
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn existing_keys_works() {
+    let hmap = filled_hmap();
+    assert_eq!(hmap.existing_keys(b"ECXA"), vec![b'C', b'A']);
+    assert_eq!(hmap.existing_keys(b"EF"), Vec::<u8>::new());
+    assert_eq!(hmap.existing_keys(core::iter::empty::<&u8>()), Vec::<u8>::new());
+}
+
+#[test]
+fn try_insert_works() {
+    let mut hmap = filled_hmap();
+    // Vacant keys are inserted.
+    assert_eq!(hmap.try_insert(b'E', 5), Ok(&mut 5));
+    *hmap.try_insert(b'F', 6).expect("key must be vacant") += 10;
+    assert_eq!(hmap.get(&b'F'), Some(&16));
+    assert_eq!(hmap.len(), 6);
+    // Occupied keys are not overwritten and the value is handed back.
+    assert_eq!(hmap.try_insert(b'A', 10), Err(OccupiedError { value: 10 }));
+    assert_eq!(hmap.get(&b'A'), Some(&1));
+    assert_eq!(hmap.len(), 6);
+}
+
+#[test]
+fn get_many_mut_works() {
+    let mut hmap = filled_hmap();
+    // Distinct keys that are all present.
+    let [a, c, d] = hmap
+        .get_many_mut([&b'A', &b'C', &b'D'])
+        .expect("all keys are present and distinct");
+    core::mem::swap(a, d);
+    *c += 10;
+    assert_eq!(hmap.get(&b'A'), Some(&4));
+    assert_eq!(hmap.get(&b'C'), Some(&13));
+    assert_eq!(hmap.get(&b'D'), Some(&1));
+    // A key requested twice.
+    assert_eq!(hmap.get_many_mut([&b'A', &b'B', &b'A']), None);
+    // A missing key.
+    assert_eq!(hmap.get_many_mut([&b'A', &b'E']), None);
+    // No keys at all.
+    assert_eq!(hmap.get_many_mut::<u8, 0>([]), Some([]));
+    assert_eq!(hmap.len(), 4);
+}
+
+#[test]
+fn get_many_mut_loads_lazily() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        push_hmap(&filled_hmap());
+        // None of the values has been loaded from the contract storage, yet.
+        let mut hmap = pull_hmap();
+        let [b, d] = hmap
+            .get_many_mut([&b'B', &b'D'])
+            .expect("all keys are present and distinct");
+        core::mem::swap(b, d);
+        assert_eq!(hmap.get_many_mut([&b'C', &b'E']), None);
+        push_hmap(&hmap);
+        let pulled = pull_hmap();
+        assert_eq!(pulled.get(&b'B'), Some(&4));
+        assert_eq!(pulled.get(&b'C'), Some(&3));
+        assert_eq!(pulled.get(&b'D'), Some(&2));
+        Ok(())
+    })
+}
+
+#[test]
+fn remove_works() {
+    let mut hmap = filled_hmap();
+    assert!(hmap.remove(&b'A'));
+    assert!(!hmap.remove(&b'A'));
+    assert!(!hmap.remove(&b'E'));
+    assert_eq!(hmap.len(), 3);
+    assert_eq!(hmap.get(&b'A'), None);
+    // The freed key index is reused.
+    hmap.insert(b'E', 5);
+    assert_eq!(
+        hmap,
+        [(b'B', 2), (b'C', 3), (b'D', 4), (b'E', 5)]
+            .iter()
+            .copied()
+            .collect::<StorageHashMap<u8, i32>>()
+    );
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn remove_frees_storage_like_take() -> ink_env::Result<()> {
+    use ink_env::DefaultEnvironment;
+    ink_env::test::run_test::<DefaultEnvironment, _>(|_| {
+        let contract = ink_env::test::get_current_contract_account_id::<
+            DefaultEnvironment,
+        >()?;
+        let used_cells =
+            || ink_env::test::count_used_storage_cells::<DefaultEnvironment>(&contract);
+        push_hmap(&filled_hmap());
+        let mut hmap = pull_hmap();
+        assert_eq!(hmap.take(&b'D'), Some(4));
+        push_hmap(&hmap);
+        let used_cells_after_take = used_cells()?;
+
+        push_hmap(&filled_hmap());
+        let mut hmap = pull_hmap();
+        assert!(hmap.remove(&b'D'));
+        assert!(!hmap.remove(&b'E'));
+        push_hmap(&hmap);
+        assert_eq!(used_cells()?, used_cells_after_take);
+        let hmap = pull_hmap();
+        assert_eq!(hmap.get(&b'D'), None);
+        assert_eq!(hmap.len(), 3);
+        Ok(())
+    })
+}
+
+#[test]
+fn remove_entry_works() {
+    let mut hmap = filled_hmap();
+    assert_eq!(hmap.remove_entry(&b'B'), Some((b'B', 2)));
+    assert_eq!(hmap.remove_entry(&b'B'), None);
+    assert_eq!(hmap.remove_entry(&b'E'), None);
+    assert_eq!(hmap.len(), 3);
+    assert_eq!(hmap.get(&b'B'), None);
+    assert_eq!(hmap.keys().copied().collect::<Vec<_>>(), b"ACD");
+}
+
+#[test]
+fn remove_if_works() {
+    let mut hmap = filled_hmap();
+    // The predicate holds: the pair is removed.
+    assert_eq!(hmap.remove_if(&b'A', |value| *value == 1), Some(1));
+    assert_eq!(hmap.get(&b'A'), None);
+    assert_eq!(hmap.len(), 3);
+    // The predicate does not hold: the pair is kept.
+    assert_eq!(hmap.remove_if(&b'B', |value| *value == 0), None);
+    assert_eq!(hmap.get(&b'B'), Some(&2));
+    assert_eq!(hmap.len(), 3);
+    // The key is missing: the predicate is never called.
+    assert_eq!(
+        hmap.remove_if(&b'E', |_| panic!("must not be called for missing keys")),
+        None
+    );
+    assert_eq!(hmap.len(), 3);
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn take_from_inconsistent_storage_panics() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        push_hmap(&filled_hmap());
+        let mut hmap = pull_hmap();
+        // Mock a corrupted value entry that points to a key index that is
+        // out of bounds for the stored keys.
+        let (_, cell_key) = hmap
+            .debug_cell_keys()
+            .into_iter()
+            .find(|(key, _)| *key == b'B')
+            .expect("key must have a value cell");
+        ink_env::set_contract_storage(&cell_key, &(2_i32, 99_u32));
+        let expected = "`key_index` must point to a valid key entry";
+        ink_env::test::assert_panics(expected, || {
+            hmap.take(&b'B');
+        });
+        Ok(())
+    })
+}
+
+#[test]
+fn swap_values_works() {
+    use super::Entry;
+    let key_index = |hmap: &mut StorageHashMap<u8, i32>, key| {
+        match hmap.entry(key) {
+            Entry::Occupied(occupied) => occupied.key_index(),
+            Entry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+    };
+    // Both keys are present: only the values are swapped.
+    let mut hmap = filled_hmap();
+    let (a_index, d_index) = (key_index(&mut hmap, b'A'), key_index(&mut hmap, b'D'));
+    assert!(hmap.swap_values(&b'A', &b'D'));
+    assert_eq!(hmap.get(&b'A'), Some(&4));
+    assert_eq!(hmap.get(&b'D'), Some(&1));
+    assert_eq!(key_index(&mut hmap, b'A'), a_index);
+    assert_eq!(key_index(&mut hmap, b'D'), d_index);
+    assert_eq!(hmap.keys().copied().collect::<Vec<_>>(), b"ABCD");
+    // The map stays consistent after removals.
+    assert_eq!(hmap.take(&b'A'), Some(4));
+    assert_eq!(hmap.take(&b'D'), Some(1));
+    assert_eq!(hmap.len(), 2);
+    // One key is missing: nothing changes.
+    let mut hmap = filled_hmap();
+    assert!(!hmap.swap_values(&b'A', &b'E'));
+    assert!(!hmap.swap_values(&b'E', &b'A'));
+    assert!(!hmap.swap_values(&b'E', &b'E'));
+    assert_eq!(hmap, filled_hmap());
+    // The same key is present: nothing changes.
+    assert!(hmap.swap_values(&b'B', &b'B'));
+    assert_eq!(hmap, filled_hmap());
+}
+
+#[test]
+fn take_reporting_works() {
+    use super::Entry;
+    let mut hmap = filled_hmap();
+    // Taking a missing key reports nothing.
+    assert_eq!(hmap.take_reporting(&b'X'), (None, None));
+    // Taking the most recent key frees the last slot without relocation.
+    assert_eq!(hmap.take_reporting(&b'D'), (Some(4), None));
+    // Taking the first key relocates the most recent key into its slot.
+    assert_eq!(hmap.take_reporting(&b'A'), (Some(1), Some((b'C', 0))));
+    match hmap.entry(b'C') {
+        Entry::Occupied(occupied) => assert_eq!(occupied.key_index(), 0),
+        Entry::Vacant(_) => panic!("expected an occupied entry"),
+    }
+    assert_eq!(hmap.get(&b'C'), Some(&3));
+    assert_eq!(hmap.len(), 2);
+    assert_eq!(hmap.keys().copied().collect::<Vec<_>>(), vec![b'C', b'B']);
+}
+
+#[test]
+fn debug_summary_works() {
+    use super::MapSummary;
+    let mut hmap = filled_hmap();
+    assert_eq!(
+        hmap.debug_summary(),
+        MapSummary {
+            len: 4,
+            capacity: 4,
+            vacant: 0
+        }
+    );
+    assert_eq!(hmap.take(&b'A'), Some(1));
+    assert_eq!(hmap.take(&b'C'), Some(3));
+    assert_eq!(
+        hmap.debug_summary(),
+        MapSummary {
+            len: 2,
+            capacity: 4,
+            vacant: 2
+        }
+    );
+    // Inserting re-uses a vacant slot.
+    assert_eq!(hmap.insert(b'E', 5), None);
+    assert_eq!(
+        hmap.debug_summary(),
+        MapSummary {
+            len: 3,
+            capacity: 4,
+            vacant: 1
+        }
+    );
+    hmap.defrag(None);
+    assert_eq!(
+        hmap.debug_summary(),
+        MapSummary {
+            len: 3,
+            capacity: 3,
+            vacant: 0
+        }
+    );
+}
+
+#[test]
+fn entry_or_insert_works_for_non_default_values() {
+    use core::num::NonZeroU128;
+    let one = NonZeroU128::new(1).unwrap();
+    let mut hmap = <StorageHashMap<u8, NonZeroU128>>::new();
+    assert_eq!(hmap.entry(b'A').or_insert(one), &mut NonZeroU128::new(1).unwrap());
+    // The existing value is kept and can be updated in place.
+    let value = hmap.entry(b'A').or_insert(one);
+    *value = NonZeroU128::new(value.get() + 1).unwrap();
+    assert_eq!(hmap.get(&b'A'), Some(&NonZeroU128::new(2).unwrap()));
+    assert_eq!(hmap.len(), 1);
+}
+
+#[test]
+fn pair_lookups_work() {
+    let mut hmap = <StorageHashMap<(u8, u32), i32>>::new();
+    assert_eq!(hmap.insert((b'A', 1), 10), None);
+    assert_eq!(hmap.insert((b'A', 2), 20), None);
+    assert_eq!(hmap.insert((b'B', 1), 30), None);
+    let (owner, spender) = (&b'A', &2);
+    assert_eq!(hmap.get_pair(owner, spender), Some(&20));
+    assert_eq!(hmap.get_pair(&b'B', &2), None);
+    assert!(hmap.contains_pair(&b'B', &1));
+    assert!(!hmap.contains_pair(&b'C', &1));
+    assert_eq!(hmap.take_pair(owner, spender), Some(20));
+    assert_eq!(hmap.take_pair(owner, spender), None);
+    assert!(!hmap.contains_pair(owner, spender));
+    assert_eq!(hmap.len(), 2);
+}
+
+#[test]
+fn pair_lookups_load_lazily() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        let hmap = [((b'A', 1), 10), ((b'A', 2), 20)]
+            .iter()
+            .copied()
+            .collect::<StorageHashMap<(u8, u32), i32>>();
+        SpreadLayout::push_spread(&hmap, &mut key_ptr());
+        let mut pulled =
+            <StorageHashMap<(u8, u32), i32> as SpreadLayout>::pull_spread(&mut key_ptr());
+        assert_eq!(pulled.get_pair(&b'A', &2), Some(&20));
+        assert!(!pulled.contains_pair(&b'B', &1));
+        assert_eq!(pulled.take_pair(&b'A', &1), Some(10));
+        assert_eq!(pulled.take_pair(&b'A', &1), None);
+        assert_eq!(pulled.len(), 1);
+        assert_eq!(pulled.keys().collect::<Vec<_>>(), vec![&(b'A', 2)]);
+        Ok(())
+    })
+}
+
+#[test]
+fn retain_works() {
+    // Removing none of the entries.
+    let mut hmap = filled_hmap();
+    assert_eq!(hmap.retain(|_, _| true), 0);
+    assert_eq!(hmap, filled_hmap());
+
+    // Removing every other entry while updating the retained ones.
+    let mut hmap = filled_hmap();
+    let removed = hmap.retain(|_, value| {
+        *value *= 10;
+        *value % 20 == 0
+    });
+    assert_eq!(removed, 2);
+    assert_eq!(hmap.len(), 2);
+    assert_eq!(hmap.keys().copied().collect::<Vec<_>>(), vec![b'B', b'D']);
+    assert_eq!(hmap.get(&b'B'), Some(&20));
+    assert_eq!(hmap.get(&b'D'), Some(&40));
+    // The vacant slots of the removed keys can still be defragmented.
+    assert_eq!(hmap.defrag(None), 2);
+    assert_eq!(hmap.keys().copied().collect::<Vec<_>>(), vec![b'D', b'B']);
+    assert_eq!(hmap.get(&b'D'), Some(&40));
+
+    // Removing all entries.
+    let mut hmap = filled_hmap();
+    assert_eq!(hmap.retain(|_, _| false), 4);
+    assert!(hmap.is_empty());
+    assert_eq!(hmap.iter().next(), None);
+}
+
+#[test]
+fn drain_works() {
+    let mut hmap = filled_hmap();
+    let mut drain = hmap.drain();
+    assert_eq!(drain.len(), 4);
+    assert_eq!(drain.next(), Some((b'A', 1)));
+    assert_eq!(drain.len(), 3);
+    assert_eq!(
+        drain.collect::<Vec<_>>(),
+        vec![(b'B', 2), (b'C', 3), (b'D', 4)]
+    );
+    assert_eq!(hmap.len(), 0);
+    assert!(hmap.is_empty());
+    assert_eq!(hmap.get(&b'A'), None);
+    // The storage of the keys has been freed.
+    assert_eq!(hmap.keys.capacity(), 0);
+    // The map can be used as before.
+    assert_eq!(hmap.insert(b'E', 5), None);
+    assert_eq!(hmap.keys().copied().collect::<Vec<_>>(), vec![b'E']);
+}
+
+#[test]
+fn partial_drain_works() {
+    let mut hmap = filled_hmap();
+    assert_eq!(
+        hmap.drain().take(2).collect::<Vec<_>>(),
+        vec![(b'A', 1), (b'B', 2)]
+    );
+    // The yielded pairs are removed while the others are left intact.
+    assert_eq!(hmap.len(), 2);
+    assert_eq!(hmap.get(&b'A'), None);
+    assert_eq!(hmap.get(&b'B'), None);
+    assert_eq!(hmap.get(&b'C'), Some(&3));
+    assert_eq!(hmap.get(&b'D'), Some(&4));
+    assert_eq!(hmap.keys().copied().collect::<Vec<_>>(), vec![b'C', b'D']);
+    // Draining again yields the remaining pairs.
+    assert_eq!(
+        hmap.drain().collect::<Vec<_>>(),
+        vec![(b'C', 3), (b'D', 4)]
+    );
+    assert!(hmap.is_empty());
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn drain_clears_storage() -> ink_env::Result<()> {
+    use ink_env::DefaultEnvironment;
+    ink_env::test::run_test::<DefaultEnvironment, _>(|_| {
+        let contract = ink_env::test::get_current_contract_account_id::<
+            DefaultEnvironment,
+        >()?;
+        let used_cells =
+            || ink_env::test::count_used_storage_cells::<DefaultEnvironment>(&contract);
+        push_hmap(&StorageHashMap::new());
+        let used_cells_empty = used_cells()?;
+        push_hmap(&filled_hmap());
+        assert!(used_cells()? > used_cells_empty);
+
+        let mut hmap = pull_hmap();
+        assert_eq!(hmap.drain().count(), 4);
+        push_hmap(&hmap);
+        assert_eq!(used_cells()?, used_cells_empty);
+        Ok(())
+    })
+}
+
+#[test]
+fn clear_works() {
+    use super::Entry;
+    // Clearing an empty hash map is a no-op.
+    let mut hmap = <StorageHashMap<u8, i32>>::new();
+    hmap.clear();
+    assert!(hmap.is_empty());
+    // Clearing a filled hash map removes all of its pairs.
+    let mut hmap = filled_hmap();
+    hmap.clear();
+    assert_eq!(hmap.len(), 0);
+    assert_eq!(hmap.iter().next(), None);
+    for key in b"ABCD" {
+        assert_eq!(hmap.get(key), None);
+    }
+    // Newly inserted keys are assigned fresh key indices.
+    match hmap.entry(b'E') {
+        Entry::Vacant(vacant) => assert_eq!(vacant.would_use_index(), 0),
+        Entry::Occupied(_) => panic!("cleared hash map must not contain any key"),
+    }
+    assert_eq!(hmap.insert(b'E', 5), None);
+    assert_eq!(hmap.len(), 1);
+    assert_eq!(hmap.get(&b'E'), Some(&5));
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn clear_clears_storage() -> ink_env::Result<()> {
+    use ink_env::DefaultEnvironment;
+    ink_env::test::run_test::<DefaultEnvironment, _>(|_| {
+        let contract = ink_env::test::get_current_contract_account_id::<
+            DefaultEnvironment,
+        >()?;
+        let used_cells =
+            || ink_env::test::count_used_storage_cells::<DefaultEnvironment>(&contract);
+        push_hmap(&StorageHashMap::new());
+        let used_cells_empty = used_cells()?;
+        push_hmap(&filled_hmap());
+        assert!(used_cells()? > used_cells_empty);
+
+        // Clearing a hash map that has not been loaded at all.
+        let mut hmap = pull_hmap();
+        hmap.clear();
+        assert!(hmap.is_empty());
+        push_hmap(&hmap);
+        assert_eq!(used_cells()?, used_cells_empty);
+
+        // Clearing a hash map after writing to it.
+        let mut hmap = pull_hmap();
+        hmap.insert(b'X', 42);
+        hmap.insert(b'Y', 43);
+        push_hmap(&hmap);
+        let mut hmap = pull_hmap();
+        assert_eq!(hmap.insert(b'Y', 44), Some(43));
+        hmap.clear();
+        push_hmap(&hmap);
+        assert_eq!(used_cells()?, used_cells_empty);
+        assert!(pull_hmap().is_empty());
+        Ok(())
+    })
+}
+
+#[test]
+fn merge_works() {
+    // Overlapping keys.
+    let mut hmap = filled_hmap();
+    let other = [(b'C', 30), (b'D', 40), (b'E', 50)]
+        .iter()
+        .copied()
+        .collect::<StorageHashMap<u8, i32>>();
+    let mut conflicts = Vec::new();
+    hmap.merge(other, |key, self_value, other_value| {
+        conflicts.push(*key);
+        *self_value += other_value;
+    });
+    conflicts.sort_unstable();
+    assert_eq!(conflicts, vec![b'C', b'D']);
+    assert_eq!(
+        hmap,
+        [(b'A', 1), (b'B', 2), (b'C', 33), (b'D', 44), (b'E', 50)]
+            .iter()
+            .copied()
+            .collect::<StorageHashMap<u8, i32>>()
+    );
+    // Keys present in both maps keep their position.
+    assert_eq!(hmap.keys().copied().collect::<Vec<_>>(), b"ABCDE");
+    // Disjoint keys.
+    let mut hmap = filled_hmap();
+    let other = [(b'E', 5), (b'F', 6)]
+        .iter()
+        .copied()
+        .collect::<StorageHashMap<u8, i32>>();
+    hmap.merge(other, |_, _, _| panic!("must only be called for overlapping keys"));
+    assert_eq!(hmap.len(), 6);
+    assert_eq!(hmap.get(&b'E'), Some(&5));
+    assert_eq!(hmap.get(&b'F'), Some(&6));
+}
+
+#[test]
+fn entry_or_insert_with_key_ref_works() {
+    let mut hmap = filled_hmap();
+    // Vacant entry: the default is computed from the key.
+    let (key, value) = hmap
+        .entry(b'E')
+        .or_insert_with_key_ref(|key| i32::from(*key - b'A' + 1));
+    assert_eq!((*key, *value), (b'E', 5));
+    *value += 10;
+    assert_eq!(hmap.get(&b'E'), Some(&15));
+    // Occupied entry: the default is not called.
+    let (key, value) = hmap
+        .entry(b'B')
+        .or_insert_with_key_ref(|_| panic!("must not be called for occupied entries"));
+    assert_eq!((*key, *value), (b'B', 2));
+    *value = 20;
+    assert_eq!(hmap.get(&b'B'), Some(&20));
+    assert_eq!(hmap.len(), 5);
+    // The key references the key stored in the keys of the map.
+    let (key, _) = hmap.entry(b'C').or_insert_with_key_ref(|_| 0);
+    let key: *const u8 = key;
+    assert!(hmap.keys().any(|stored| core::ptr::eq(stored, key)));
+}
+
+#[test]
+fn entry_and_try_modify_works() {
+    let mut hmap = filled_hmap();
+    // Successful modification of an occupied entry.
+    let value = hmap
+        .entry(b'B')
+        .and_try_modify(|value| {
+            *value = value.checked_add(10).ok_or("overflow")?;
+            Ok::<_, &str>(())
+        })
+        .unwrap()
+        .or_insert(0);
+    assert_eq!(*value, 12);
+    assert_eq!(hmap.get(&b'B'), Some(&12));
+    // Vacant entries are left alone and can still be inserted into.
+    let value = hmap
+        .entry(b'E')
+        .and_try_modify(|_| Err("must not be called for vacant entries"))
+        .unwrap()
+        .or_insert(5);
+    assert_eq!(*value, 5);
+    assert_eq!(hmap.len(), 5);
+}
+
+#[test]
+fn entry_and_try_modify_failure_does_not_write() {
+    let mut hmap = filled_hmap();
+    hmap.insert(b'B', i32::MAX - 1);
+    // The closure mutates the value before failing on overflow.
+    let result = hmap.entry(b'B').and_try_modify(|value| {
+        *value += 1;
+        *value = value.checked_add(1).ok_or("overflow")?;
+        Ok(())
+    });
+    assert_eq!(result.err(), Some("overflow"));
+    assert_eq!(hmap.get(&b'B'), Some(&(i32::MAX - 1)));
+    assert_eq!(hmap.len(), 4);
+}
+
+#[test]
+fn merge_with_works() {
+    let mut hmap = filled_hmap();
+    let other = [(b'C', 30), (b'D', 40), (b'E', 50)]
+        .iter()
+        .copied()
+        .collect::<StorageHashMap<u8, i32>>();
+    let mut conflicts = Vec::new();
+    hmap.merge_with(other, |key, self_value, other_value| {
+        conflicts.push(*key);
+        self_value + other_value
+    });
+    conflicts.sort_unstable();
+    assert_eq!(conflicts, vec![b'C', b'D']);
+    let expected = [(b'A', 1), (b'B', 2), (b'C', 33), (b'D', 44), (b'E', 50)]
+        .iter()
+        .copied()
+        .collect::<StorageHashMap<u8, i32>>();
+    assert_eq!(hmap, expected);
+    // Merging an empty map changes nothing.
+    hmap.merge_with(StorageHashMap::new(), |_, _, _| unreachable!());
+    assert_eq!(hmap, expected);
+}
+
+#[test]
+fn keys_recent_works() {
+    let mut hmap = filled_hmap();
+    assert_eq!(
+        hmap.keys_recent().copied().collect::<Vec<_>>(),
+        vec![b'D', b'C', b'B', b'A']
+    );
+    hmap.insert(b'E', 5);
+    // Updating a value does not change the insertion order.
+    hmap.insert(b'A', 10);
+    assert_eq!(
+        hmap.keys_recent().take(2).copied().collect::<Vec<_>>(),
+        vec![b'E', b'D']
+    );
+    assert_eq!(<StorageHashMap<u8, i32>>::new().keys_recent().next(), None);
+}
+
+#[test]
+fn retain_fold_works() {
+    let mut hmap = filled_hmap();
+    // Remove all entries below 3 while summing up their values.
+    let removed_sum = hmap.retain_fold(0, |sum, _key, value| {
+        if *value < 3 {
+            *sum += *value;
+            return false
+        }
+        true
+    });
+    assert_eq!(removed_sum, 1 + 2);
+    assert_eq!(hmap.len(), 2);
+    assert!(!hmap.contains_key(&b'A'));
+    assert!(!hmap.contains_key(&b'B'));
+    assert_eq!(hmap.get(&b'C'), Some(&3));
+    assert_eq!(hmap.get(&b'D'), Some(&4));
+    // Retained values may be mutated.
+    let visited = hmap.retain_fold(0, |count, _key, value| {
+        *value *= 10;
+        *count += 1;
+        true
+    });
+    assert_eq!(visited, 2);
+    assert_eq!(hmap.get(&b'C'), Some(&30));
+    assert_eq!(hmap.get(&b'D'), Some(&40));
+}
+
+#[test]
+fn retain_fold_empty_works() {
+    let mut hmap = <StorageHashMap<u8, i32>>::new();
+    assert_eq!(hmap.retain_fold(7, |_, _, _| false), 7);
+    assert!(hmap.is_empty());
+}
+
+/// A value type implementing neither `Default`, `Eq` nor `Debug`.
+#[derive(scale::Encode, scale::Decode)]
+struct Opaque(u8);
+
+impl SpreadLayout for Opaque {
+    const FOOTPRINT: u64 = 1;
+    const REQUIRES_DEEP_CLEAN_UP: bool = false;
+
+    fn pull_spread(ptr: &mut KeyPtr) -> Self {
+        forward_pull_packed::<Self>(ptr)
+    }
+
+    fn push_spread(&self, ptr: &mut KeyPtr) {
+        forward_push_packed::<Self>(self, ptr)
+    }
+
+    fn clear_spread(&self, ptr: &mut KeyPtr) {
+        forward_clear_packed::<Self>(self, ptr)
+    }
+}
+
+impl PackedLayout for Opaque {
+    fn pull_packed(&mut self, _at: &Key) {}
+    fn push_packed(&self, _at: &Key) {}
+    fn clear_packed(&self, _at: &Key) {}
+}
+
+#[test]
+fn entry_api_works_without_default_eq_debug() {
+    let mut hmap = <StorageHashMap<u8, Opaque>>::new();
+    assert_eq!(hmap.entry(b'A').key(), &b'A');
+    hmap.entry(b'A').or_insert(Opaque(1));
+    hmap.entry(b'A').and_modify(|value| value.0 += 1).or_insert(Opaque(0));
+    hmap.entry(b'B').and_modify(|value| value.0 += 1).or_insert(Opaque(5));
+    assert_eq!(hmap.get(&b'A').map(|value| value.0), Some(2));
+    assert_eq!(hmap.get(&b'B').map(|value| value.0), Some(5));
+}
+
+#[test]
+fn read_page_works() {
+    let mut hmap = (0..25)
+        .map(|n| (n, i32::from(n) * 10))
+        .collect::<StorageHashMap<u8, i32>>();
+    let mut pages = Vec::new();
+    let mut cursor = Some(0);
+    while let Some(start_index) = cursor {
+        let (page, next) = hmap.read_page(start_index, 10);
+        pages.push(page);
+        cursor = next;
+    }
+    assert_eq!(
+        pages.iter().map(|page| page.len()).collect::<Vec<_>>(),
+        vec![10, 10, 5]
+    );
+    let entries = pages.concat();
+    let expected = (0..25).map(|n| (n, i32::from(n) * 10)).collect::<Vec<_>>();
+    assert_eq!(entries, expected);
+
+    // Vacant slots are skipped.
+    for n in (0..25).step_by(2) {
+        assert!(hmap.take(&n).is_some());
+    }
+    let (page, next) = hmap.read_page(0, 10);
+    let expected = (1..20)
+        .step_by(2)
+        .map(|n| (n, i32::from(n) * 10))
+        .collect::<Vec<_>>();
+    assert_eq!(page, expected);
+    assert_eq!(next, Some(20));
+    let (page, next) = hmap.read_page(20, 10);
+    assert_eq!(page, vec![(21, 210), (23, 230)]);
+    assert_eq!(next, None);
+    // Reading out of bounds yields nothing.
+    assert_eq!(hmap.read_page(100, 10), (Vec::new(), None));
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn try_get_works() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        push_hmap(&filled_hmap());
+        let hmap = pull_hmap();
+        // Mock a corrupt cell by overwriting the value of `B` with an empty one.
+        let corrupt_key = hmap.values.key_at(&b'B').expect("must have a storage key");
+        ink_env::set_contract_storage(&corrupt_key, &());
+
+        assert_eq!(hmap.try_get(&b'A'), Ok(Some(&1)));
+        assert_eq!(hmap.try_get(&b'E'), Ok(None));
+        assert_eq!(hmap.try_get(&b'B'), Err(DecodeError));
+        // Failed loads are not cached.
+        assert_eq!(hmap.try_get(&b'B'), Err(DecodeError));
+        // Values that are already loaded are not affected.
+        assert_eq!(hmap.get(&b'C'), Some(&3));
+        let corrupt_key = hmap.values.key_at(&b'C').expect("must have a storage key");
+        ink_env::set_contract_storage(&corrupt_key, &());
+        assert_eq!(hmap.try_get(&b'C'), Ok(Some(&3)));
+        Ok(())
+    })
+}
+
+#[test]
+fn spread_layout_push_pull_works() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        let hmap1 = filled_hmap();
+        push_hmap(&hmap1);
+        // Load the pushed storage hmap into another instance and check that
+        // both instances are equal:
+        let hmap2 = pull_hmap();
+        assert_eq!(hmap1, hmap2);
+        Ok(())
+    })
+}
+
+#[test]
+#[should_panic(expected = "storage entry was empty")]
+fn spread_layout_clear_works() {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        let hmap1 = filled_hmap();
+        let root_key = Key::from([0x42; 32]);
+        SpreadLayout::push_spread(&hmap1, &mut KeyPtr::from(root_key));
+        // It has already been asserted that a valid instance can be pulled
+        // from contract storage after a push to the same storage region.
+        //
+        // Now clear the associated storage from `hmap1` and check whether
+        // loading another instance from this storage will panic since the
+        // hmap's length property cannot read a value:
+        SpreadLayout::clear_spread(&hmap1, &mut KeyPtr::from(root_key));
+        let _ = <StorageHashMap<u8, i32> as SpreadLayout>::pull_spread(
+            &mut KeyPtr::from(root_key),
+        );
+        Ok(())
+    })
+    .unwrap()
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn storage_is_cleared_completely_after_pull_lazy() {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        // given
+        let root_key = Key::from([0x42; 32]);
+        let lazy_hmap = Lazy::new(filled_hmap());
+        SpreadLayout::push_spread(&lazy_hmap, &mut KeyPtr::from(root_key));
+        let pulled_hmap = <Lazy<StorageHashMap<u8, i32>> as SpreadLayout>::pull_spread(
+            &mut KeyPtr::from(root_key),
+        );
+
+        // when
+        SpreadLayout::clear_spread(&pulled_hmap, &mut KeyPtr::from(root_key));
+
+        // then
+        let contract_id = ink_env::test::get_current_contract_account_id::<
+            ink_env::DefaultEnvironment,
+        >()
+        .expect("Cannot get contract id");
+        let used_cells = ink_env::test::count_used_storage_cells::<
+            ink_env::DefaultEnvironment,
+        >(&contract_id)
+        .expect("used cells must be returned");
+        assert_eq!(used_cells, 0);
+
+        Ok(())
+    })
+    .unwrap()
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn contract_storage_is_empty_after_clear_and_terminate() -> ink_env::Result<()> {
+    use ink_env::DefaultEnvironment;
+    ink_env::test::run_test::<DefaultEnvironment, _>(|accounts| {
+        assert!(ink_env::test::contract_storage_is_empty::<DefaultEnvironment>()?);
+        push_hmap(&filled_hmap());
+        assert!(!ink_env::test::contract_storage_is_empty::<DefaultEnvironment>()?);
+
+        // A `destroy` message first clears the map and then terminates.
+        let hmap = pull_hmap();
+        SpreadLayout::clear_spread(&hmap, &mut key_ptr());
+        // Dropping a pulled map clears its cells once more which has to happen
+        // while the contract still exists.
+        drop(hmap);
+        assert!(ink_env::test::contract_storage_is_empty::<DefaultEnvironment>()?);
+        ink_env::test::assert_contract_termination::<DefaultEnvironment, _>(
+            || ink_env::terminate_contract::<DefaultEnvironment>(accounts.alice),
+            accounts.alice,
+            0,
+        );
+        assert!(ink_env::test::contract_storage_is_empty::<DefaultEnvironment>()?);
+        Ok(())
+    })
+}
+
+#[test]
+#[should_panic(expected = "storage entry was empty")]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn drop_works() {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        let root_key = Key::from([0x42; 32]);
+
+        // if the setup panics it should not cause the test to pass
+        let setup_result = std::panic::catch_unwind(|| {
+            let hmap = filled_hmap();
+            SpreadLayout::push_spread(&hmap, &mut KeyPtr::from(root_key));
+            let _ = <StorageHashMap<u8, i32> as SpreadLayout>::pull_spread(
+                &mut KeyPtr::from(root_key),
+            );
+            // hmap is dropped which should clear the cells
+        });
+        assert!(setup_result.is_ok(), "setup should not panic");
+
+        let contract_id = ink_env::test::get_current_contract_account_id::<
+            ink_env::DefaultEnvironment,
+        >()
+        .expect("Cannot get contract id");
+        let used_cells = ink_env::test::count_used_storage_cells::<
+            ink_env::DefaultEnvironment,
+        >(&contract_id)
+        .expect("used cells must be returned");
+        assert_eq!(used_cells, 0);
+
+        let _ = <StorageHashMap<u8, i32> as SpreadLayout>::pull_spread(
+            &mut KeyPtr::from(root_key),
+        );
+        Ok(())
+    })
+    .unwrap()
+}
+
+#[test]
+#[cfg(feature = "ink-experimental-engine")]
+fn storage_is_cleared_completely_after_pull_lazy() {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        // given
+        let root_key = Key::from([0x42; 32]);
+        let lazy_hmap = Lazy::new(filled_hmap());
+        SpreadLayout::push_spread(&lazy_hmap, &mut KeyPtr::from(root_key));
+        let pulled_hmap = <Lazy<StorageHashMap<u8, i32>> as SpreadLayout>::pull_spread(
+            &mut KeyPtr::from(root_key),
+        );
+
+        // when
+        SpreadLayout::clear_spread(&pulled_hmap, &mut KeyPtr::from(root_key));
+
+        // then
+        let contract_id = ink_env::test::callee::<ink_env::DefaultEnvironment>();
+        let used_cells = ink_env::test::count_used_storage_cells::<
+            ink_env::DefaultEnvironment,
+        >(&contract_id)
+        .expect("used cells must be returned");
+        assert_eq!(used_cells, 0);
+
+        Ok(())
+    })
+    .unwrap()
+}
+
+#[test]
+#[should_panic(expected = "storage entry was empty")]
+#[cfg(feature = "ink-experimental-engine")]
+fn drop_works() {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        let root_key = Key::from([0x42; 32]);
+
+        // if the setup panics it should not cause the test to pass
+        let setup_result = std::panic::catch_unwind(|| {
+            let hmap = filled_hmap();
+            SpreadLayout::push_spread(&hmap, &mut KeyPtr::from(root_key));
+            let _ = <StorageHashMap<u8, i32> as SpreadLayout>::pull_spread(
+                &mut KeyPtr::from(root_key),
+            );
+            // hmap is dropped which should clear the cells
+        });
+        assert!(setup_result.is_ok(), "setup should not panic");
+
+        let contract_id = ink_env::test::callee::<ink_env::DefaultEnvironment>();
+        let used_cells = ink_env::test::count_used_storage_cells::<
+            ink_env::DefaultEnvironment,
+        >(&contract_id)
+        .expect("used cells must be returned");
+        assert_eq!(used_cells, 0);
+
+        let _ = <StorageHashMap<u8, i32> as SpreadLayout>::pull_spread(
+            &mut KeyPtr::from(root_key),
+        );
+        Ok(())
+    })
+    .unwrap()
+}
+
+#[test]
+fn partial_eq_works() {
+    // Equal maps regardless of their insertion order.
+    let reversed = [(b'D', 4), (b'C', 3), (b'B', 2), (b'A', 1)]
+        .iter()
+        .copied()
+        .collect::<StorageHashMap<u8, i32>>();
+    assert_eq!(filled_hmap(), reversed);
+    assert_eq!(reversed, filled_hmap());
+    // Maps differing by one value.
+    let mut different_value = filled_hmap();
+    different_value.insert(b'C', 30);
+    assert_ne!(filled_hmap(), different_value);
+    assert_ne!(different_value, filled_hmap());
+    // Maps of different lengths.
+    let mut longer = filled_hmap();
+    longer.insert(b'E', 5);
+    assert_ne!(filled_hmap(), longer);
+    assert_ne!(longer, filled_hmap());
+    // Maps of equal lengths with different keys.
+    let mut different_key = filled_hmap();
+    different_key.take(&b'D');
+    different_key.insert(b'E', 4);
+    assert_ne!(filled_hmap(), different_key);
+}
+
+#[test]
+fn clone_works() {
+    let hmap = filled_hmap();
+    let mut clone = hmap.clone();
+    assert_eq!(clone, hmap);
+    assert!(clone.iter().eq(hmap.iter()));
+    // Mutating the clone leaves the original unchanged.
+    clone.insert(b'A', 10);
+    clone.insert(b'E', 5);
+    clone.take(&b'B');
+    assert_eq!(hmap, filled_hmap());
+    assert_eq!(clone.len(), 4);
+    assert_eq!(clone.get(&b'A'), Some(&10));
+}
+
+#[test]
+fn clone_of_pulled_hmap_is_detached() {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        push_hmap(&filled_hmap());
+        let pulled = pull_hmap();
+        let mut clone = pulled.clone();
+        assert_eq!(clone, pulled);
+        clone.insert(b'A', 10);
+        clone.take(&b'B');
+        // Dropping the clone must not touch the storage of the original.
+        drop(clone);
+        assert_eq!(pulled, filled_hmap());
+        Ok(())
+    })
+    .unwrap()
+}
+
+#[test]
+fn insert_new_works() {
+    let mut hmap = filled_hmap();
+    // New key.
+    assert!(hmap.insert_new(b'E', 5));
+    assert_eq!(hmap.get(&b'E'), Some(&5));
+    assert_eq!(hmap.len(), 5);
+    // Overwriting an existing key.
+    assert!(!hmap.insert_new(b'A', 10));
+    assert_eq!(hmap.get(&b'A'), Some(&10));
+    assert_eq!(hmap.len(), 5);
+}
+
+#[test]
+fn into_iter_works() {
+    let hmap = filled_hmap();
+    let mut iter = hmap.into_iter();
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.next(), Some((b'A', 1)));
+    assert_eq!(iter.len(), 3);
+    let mut pairs = iter.collect::<Vec<_>>();
+    pairs.sort_unstable();
+    assert_eq!(pairs, vec![(b'B', 2), (b'C', 3), (b'D', 4)]);
+    // Owned pairs can be used to rebuild a different structure.
+    let rebuilt = filled_hmap()
+        .into_iter()
+        .collect::<std::collections::BTreeMap<_, _>>();
+    assert_eq!(
+        rebuilt.into_iter().collect::<Vec<_>>(),
+        vec![(b'A', 1), (b'B', 2), (b'C', 3), (b'D', 4)]
+    );
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn into_iter_clears_storage() -> ink_env::Result<()> {
+    use ink_env::DefaultEnvironment;
+    ink_env::test::run_test::<DefaultEnvironment, _>(|_| {
+        let contract = ink_env::test::get_current_contract_account_id::<
+            DefaultEnvironment,
+        >()?;
+        let used_cells =
+            || ink_env::test::count_used_storage_cells::<DefaultEnvironment>(&contract);
+        push_hmap(&filled_hmap());
+        let mut pairs = pull_hmap().into_iter().collect::<Vec<_>>();
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(b'A', 1), (b'B', 2), (b'C', 3), (b'D', 4)]);
+        assert_eq!(used_cells()?, 0);
+
+        // Dropping a partially consumed iterator clears the remaining pairs.
+        push_hmap(&filled_hmap());
+        assert_eq!(pull_hmap().into_iter().take(2).count(), 2);
+        assert_eq!(used_cells()?, 0);
+        Ok(())
+    })
+}
+
+#[test]
+fn retain_compact_works() {
+    let mut hmap = (0..20)
+        .map(|n| (n, i32::from(n) * 10))
+        .collect::<StorageHashMap<u8, i32>>();
+    // Retain a sparse subset while updating the retained values.
+    let removed = hmap.retain_compact(|key, value| {
+        *value += 1;
+        key % 5 == 0
+    });
+    assert_eq!(removed, 16);
+    assert_eq!(hmap.len(), 4);
+    assert_eq!(hmap.capacity(), hmap.len());
+    assert_eq!(hmap.keys().copied().collect::<Vec<_>>(), vec![0, 5, 10, 15]);
+    for key in [0, 5, 10, 15] {
+        assert_eq!(hmap.get(&key), Some(&(i32::from(key) * 10 + 1)));
+        let (stored_key, _) = hmap.get_key_value(&key).unwrap();
+        assert_eq!(*stored_key, key);
+    }
+    assert_eq!(hmap.get(&1), None);
+    assert_eq!(hmap.defrag(None), 0);
+    // Removing and inserting keeps the map consistent.
+    assert_eq!(hmap.take(&5), Some(51));
+    assert_eq!(hmap.insert(7, 70), None);
+    assert_eq!(hmap.get(&15), Some(&151));
+    assert_eq!(hmap.len(), 4);
+}
+
+#[test]
+fn retain_compact_survives_push_pull() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        push_hmap(&filled_hmap());
+        let mut hmap = pull_hmap();
+        assert_eq!(hmap.retain_compact(|key, _| *key == b'B' || *key == b'D'), 2);
+        assert_eq!(hmap.capacity(), 2);
+        push_hmap(&hmap);
+        let pulled = pull_hmap();
+        assert_eq!(pulled.capacity(), 2);
+        assert_eq!(
+            pulled.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            vec![(b'B', 2), (b'D', 4)]
+        );
+        assert_eq!(pulled.get_key_value(&b'D'), Some((&b'D', &4)));
+        Ok(())
+    })
+}
+
+/// Returns a hash map whose keys are not stored contiguously.
+fn holey_hmap() -> StorageHashMap<u8, i32> {
+    let mut hmap = (b'A'..=b'H')
+        .zip(1..)
+        .collect::<StorageHashMap<u8, i32>>();
+    for key in b"BEF" {
+        hmap.take(key);
+    }
+    hmap
+}
+
+#[test]
+fn iter_rev_yields_same_set() {
+    let mut hmap = holey_hmap();
+    let forward = hmap.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>();
+    let mut backward = hmap.iter().rev().map(|(k, v)| (*k, *v)).collect::<Vec<_>>();
+    backward.reverse();
+    assert_eq!(forward, backward);
+    let mut keys = hmap.keys().rev().copied().collect::<Vec<_>>();
+    keys.reverse();
+    assert_eq!(keys, hmap.keys().copied().collect::<Vec<_>>());
+    let mut values = hmap.values().rev().copied().collect::<Vec<_>>();
+    values.reverse();
+    assert_eq!(values, hmap.values().copied().collect::<Vec<_>>());
+    for (_, value) in hmap.iter_mut().rev() {
+        *value *= 10;
+    }
+    assert_eq!(
+        hmap.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        forward.iter().map(|(k, v)| (*k, v * 10)).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn iter_next_and_next_back_interleave() {
+    let hmap = holey_hmap();
+    let expected = hmap.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>();
+    assert_eq!(expected.len(), 5);
+    let mut iter = hmap.iter();
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    while let Some((k, v)) = iter.next() {
+        front.push((*k, *v));
+        assert_eq!(iter.len(), expected.len() - front.len() - back.len());
+        match iter.next_back() {
+            Some((k, v)) => back.push((*k, *v)),
+            None => break,
+        }
+    }
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+    back.reverse();
+    front.extend(back);
+    assert_eq!(front, expected);
+
+    let mut keys = hmap.keys();
+    assert_eq!(keys.next_back(), Some(&b'H'));
+    assert_eq!(keys.next(), Some(&b'A'));
+    assert_eq!(keys.next_back(), Some(&b'G'));
+    assert_eq!(keys.next(), Some(&b'C'));
+    assert_eq!(keys.next(), Some(&b'D'));
+    assert_eq!(keys.next_back(), None);
+}
+
+#[test]
+fn iter_len_works() {
+    let mut hmap = holey_hmap();
+    assert_eq!(hmap.len(), 5);
+    let mut iter = hmap.iter();
+    assert_eq!(iter.len(), hmap.len() as usize);
+    for remaining in (0..5).rev() {
+        assert!(iter.next().is_some());
+        assert_eq!(iter.len(), remaining);
+        assert_eq!(iter.size_hint(), (remaining, Some(remaining)));
+    }
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.len(), 0);
+
+    let mut keys = hmap.keys();
+    let mut values = hmap.values();
+    assert_eq!((keys.len(), values.len()), (5, 5));
+    keys.next();
+    values.next_back();
+    assert_eq!((keys.len(), values.len()), (4, 4));
+
+    let len = hmap.len() as usize;
+    let mut iter_mut = hmap.iter_mut();
+    assert_eq!(iter_mut.len(), len);
+    iter_mut.next();
+    iter_mut.next_back();
+    assert_eq!(iter_mut.size_hint(), (len - 2, Some(len - 2)));
+}
+
+#[test]
+fn eq_ignoring_works() {
+    let hmap = filled_hmap();
+    // Differing only in the value of an ignored key.
+    let mut other = filled_hmap();
+    other.insert(b'D', 40);
+    assert_ne!(hmap, other);
+    assert!(hmap.eq_ignoring(&other, b"D"));
+    assert!(other.eq_ignoring(&hmap, b"D"));
+    // Differing only in the presence of an ignored key.
+    other.take(&b'D');
+    assert!(hmap.eq_ignoring(&other, b"D"));
+    assert!(other.eq_ignoring(&hmap, b"D"));
+    other.insert(b'E', 5);
+    assert!(hmap.eq_ignoring(&other, b"DE"));
+    // Differing in a key that is not ignored.
+    assert!(!hmap.eq_ignoring(&other, b"D"));
+    assert!(!other.eq_ignoring(&hmap, b"D"));
+    other.take(&b'E');
+    other.insert(b'A', 10);
+    assert!(!hmap.eq_ignoring(&other, b"D"));
+    assert!(other.eq_ignoring(&hmap, b"AD"));
+    // Ignoring nothing is the same as regular equality.
+    assert!(hmap.eq_ignoring(&filled_hmap(), &[]));
+    assert!(!hmap.eq_ignoring(&other, &[]));
 }
@@ -31,7 +31,10 @@ pub use self::{
     binary_heap::BinaryHeap,
     bitstash::BitStash,
     bitvec::Bitvec,
-    hashmap::HashMap,
+    hashmap::{
+        HashMap,
+        LargeHashMap,
+    },
     stash::Stash,
     vec::Vec,
 };
@@ -219,6 +219,11 @@ where
             None
         }
     }
+
+    /// Returns the index that the next element put into the stash is stored at.
+    pub(crate) fn next_put_index(&self) -> Index {
+        self.last_vacant_index().unwrap_or(self.header.len_entries)
+    }
 }
 
 impl<T> Stash<T>
@@ -429,9 +434,12 @@ where
         } else {
             // Push the new element to the end if all entries are occupied.
             let new_index = self.header.len_entries;
+            let len_entries = new_index
+                .checked_add(1)
+                .expect("cannot store more than `u32::MAX` elements in a stash");
             self.entries.put(new_index, new_entry);
             self.header.last_vacant += 1;
-            self.header.len_entries += 1;
+            self.header.len_entries = len_entries;
             new_index
         };
         self.header.len += 1;
@@ -511,6 +519,84 @@ where
         Some(())
     }
 
+    /// Ensures that the stash has vacant entries for at least `additional`
+    /// more elements by appending vacant entries if required.
+    ///
+    /// # Note
+    ///
+    /// The appended vacant entries occupy contract storage until they are
+    /// either filled by [`Stash::put`] or freed by [`Stash::defrag`].
+    ///
+    /// # Panics
+    ///
+    /// If the capacity would exceed `u32::MAX`.
+    pub fn reserve(&mut self, additional: u32) {
+        let len_vacant = self.len_entries() - self.len();
+        for _ in len_vacant..additional {
+            let at = self.len_entries();
+            // The neighbors have to be determined before the new vacant entry
+            // is accounted for since they are looked up via the header.
+            let had_vacant_entries = self.has_vacant_entries();
+            let (prev, next) = self.fetch_prev_and_next_vacant_entry(at);
+            self.header.len_entries = at
+                .checked_add(1)
+                .expect("cannot store more than `u32::MAX` elements in a stash");
+            self.entries
+                .put(at, Some(Entry::Vacant(VacantEntry { next, prev })));
+            self.update_neighboring_vacant_entry_links(prev, next, at);
+            if had_vacant_entries {
+                use core::cmp::min;
+                self.header.last_vacant =
+                    min(self.header.last_vacant, min(at, min(prev, next)));
+            } else {
+                // The appended entry is the only vacant entry.
+                self.header.last_vacant = at;
+            }
+        }
+    }
+
+    /// Retains only the elements for which `f` returns `true` and moves the
+    /// retained elements to the lowest indices in the same pass.
+    ///
+    /// Returns the number of removed elements.
+    ///
+    /// This might invalidate indices stored outside the stash.
+    ///
+    /// # Callback
+    ///
+    /// In order to keep those indices up-to-date `f` is called with the index
+    /// that the element is going to be stored at if it is retained.
+    ///
+    /// # Note
+    ///
+    /// - The retained elements keep their relative order.
+    /// - Afterwards the stash has no vacant entries left, so there is nothing
+    ///   for a subsequent [`Stash::defrag`] to free.
+    pub fn retain_compact<F>(&mut self, mut f: F) -> u32
+    where
+        F: FnMut(Index, &mut T) -> bool,
+    {
+        let mut new_len = 0;
+        for index in 0..self.len_entries() {
+            let entry = self
+                .entries
+                .put_get(index, None)
+                .expect("index is out of bounds");
+            if let Entry::Occupied(mut value) = entry {
+                if f(new_len, &mut value) {
+                    self.entries.put(new_len, Some(Entry::Occupied(value)));
+                    new_len += 1;
+                }
+            }
+        }
+        let removed = self.header.len - new_len;
+        // All remaining entries are occupied.
+        self.header.len = new_len;
+        self.header.len_entries = new_len;
+        self.header.last_vacant = new_len;
+        removed
+    }
+
     /// Defragments the underlying storage to minimize footprint.
     ///
     /// Returns the number of storage cells freed this way.
@@ -171,6 +171,39 @@ fn remove_works_with_spread_layout_push_pull() -> ink_env::Result<()> {
     })
 }
 
+#[test]
+fn reserve_works() {
+    let mut stash = [b'A', b'B', b'C'].iter().copied().collect::<StorageStash<_>>();
+    assert_eq!(stash.capacity(), 3);
+    stash.reserve(2);
+    assert_eq!(stash.capacity(), 5);
+    assert_eq!(stash.len(), 3);
+    assert_eq!(stash.iter().copied().collect::<Vec<_>>(), vec![b'A', b'B', b'C']);
+    // Vacant entries count towards the reservation.
+    assert_eq!(stash.take(1), Some(b'B'));
+    stash.reserve(3);
+    assert_eq!(stash.capacity(), 5);
+    stash.reserve(4);
+    assert_eq!(stash.capacity(), 6);
+    stash.reserve(0);
+    assert_eq!(stash.capacity(), 6);
+    // The reserved entries are filled before the stash grows.
+    assert_eq!(stash.put(b'D'), 1);
+    assert_eq!(stash.put(b'E'), 3);
+    assert_eq!(stash.put(b'F'), 4);
+    assert_eq!(stash.put(b'G'), 5);
+    assert_eq!(stash.put(b'H'), 6);
+    assert_eq!(stash.capacity(), 7);
+    assert_eq!(stash.len(), 7);
+    // Reserved entries are freed by defragmentation.
+    let mut stash = [b'A'].iter().copied().collect::<StorageStash<_>>();
+    stash.reserve(4);
+    assert_eq!(stash.capacity(), 5);
+    stash.defrag(None, |_, _, _| {});
+    assert_eq!(stash.capacity(), 1);
+    assert_eq!(stash.get(0), Some(&b'A'));
+}
+
 #[test]
 fn get_works() {
     let test_values = [b'A', b'B', b'C', b'D', b'E', b'F'];
@@ -393,6 +426,33 @@ struct EntryMove {
     value: u8,
 }
 
+#[test]
+fn retain_compact_works() {
+    // Retains B and F out of the remaining B, D and F at indices 1, 3 and 5.
+    let mut stash = create_holey_stash();
+    let mut new_indices = Vec::new();
+    let removed = stash.retain_compact(|new_index, value| {
+        let retain = *value != b'D';
+        if retain {
+            new_indices.push((*value, new_index));
+        }
+        retain
+    });
+    assert_eq!(removed, 1);
+    assert_eq!(new_indices, vec![(b'B', 0), (b'F', 1)]);
+    assert_eq!(stash.len(), 2);
+    assert_eq!(stash.len_entries(), 2);
+    assert_eq!(stash.iter().copied().collect::<Vec<_>>(), vec![b'B', b'F']);
+    // New elements are appended since there are no vacant entries left.
+    assert_eq!(stash.put(b'G'), 2);
+    assert_eq!(stash.defrag(None, |_, _, _| ()), 0);
+    // Retaining nothing leaves an empty stash.
+    assert_eq!(stash.retain_compact(|_, _| false), 3);
+    assert!(stash.is_empty());
+    assert_eq!(stash.len_entries(), 0);
+    assert_eq!(stash.put(b'H'), 0);
+}
+
 #[test]
 fn simple_defrag_works() {
     let mut stash = [b'A', b'B', b'C', b'D', b'E', b'F']
@@ -485,6 +485,55 @@ where
         Some(())
     }
 
+    /// Inserts `value` at position `index` within the vector, shifting all
+    /// elements after it up by one.
+    ///
+    /// # Note
+    ///
+    /// This operation preserves ordering but touches every element after `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is greater than the length of the vector.
+    pub fn insert(&mut self, index: u32, value: T) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+        self.push(value);
+        for i in (index..len).rev() {
+            self.elems.swap(i, i + 1);
+        }
+    }
+
+    /// Removes and returns the element at position `index` within the vector,
+    /// shifting all elements after it down by one.
+    ///
+    /// # Note
+    ///
+    /// This operation preserves ordering but touches every element after `index`.
+    /// Prefer [`Vec::swap_remove`] if the ordering does not matter.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn remove(&mut self, index: u32) -> T {
+        let len = self.len();
+        assert!(
+            index < len,
+            "removal index (is {}) should be < len (is {})",
+            index,
+            len
+        );
+        for i in index..len - 1 {
+            self.elems.swap(i, i + 1);
+        }
+        self.pop().expect("the vector must have an element at the removal index")
+    }
+
     /// Sets the elements at the given index to the new value.
     ///
     /// Won't return the old element back to the caller.
@@ -515,4 +564,31 @@ where
         }
         *self.len = 0;
     }
+
+    /// Retains only the elements for which `f` returns `true`.
+    ///
+    /// # Note
+    ///
+    /// The retained elements keep their relative order and are moved towards
+    /// the front of the vector. The storage cells freed at its tail are cleared
+    /// once the vector is synchronized with the contract storage.
+    ///
+    /// This loads every element of the vector from the contract storage.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut new_len = 0;
+        for index in 0..self.len() {
+            let value = self
+                .elems
+                .put_get(index, None)
+                .expect("index must be within bounds");
+            if f(&value) {
+                self.elems.put(new_len, Some(value));
+                new_len += 1;
+            }
+        }
+        *self.len = new_len;
+    }
 }
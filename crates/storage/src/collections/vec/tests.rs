@@ -428,6 +428,119 @@ fn clear_works_on_empty_vec() {
     assert!(vec.is_empty());
 }
 
+#[test]
+fn retain_works() {
+    // Removing from the front.
+    let mut vec = vec_from_slice(&[1, 2, 3, 4, 5]);
+    vec.retain(|value| *value > 2);
+    assert_eq!(vec.len(), 3);
+    assert_eq!(vec.iter().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+    // Removing from the middle.
+    let mut vec = vec_from_slice(&[1, 2, 3, 4, 5]);
+    vec.retain(|value| *value != 2 && *value != 4);
+    assert_eq!(vec.len(), 3);
+    assert_eq!(vec.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5]);
+    assert_eq!(vec.get(3), None);
+    // Removing nothing at all.
+    let mut vec = vec_from_slice(&[1, 2, 3]);
+    vec.retain(|_| true);
+    assert_eq!(vec, vec_from_slice(&[1, 2, 3]));
+    // Removing everything.
+    vec.retain(|_| false);
+    assert!(vec.is_empty());
+    // The vector can be used as before.
+    vec.push(4);
+    assert_eq!(vec.iter().copied().collect::<Vec<_>>(), vec![4]);
+}
+
+#[test]
+#[cfg(not(feature = "ink-experimental-engine"))]
+fn retain_clears_freed_storage() -> ink_env::Result<()> {
+    use ink_env::DefaultEnvironment;
+    ink_env::test::run_test::<DefaultEnvironment, _>(|_| {
+        let contract = ink_env::test::get_current_contract_account_id::<
+            DefaultEnvironment,
+        >()?;
+        let used_cells =
+            || ink_env::test::count_used_storage_cells::<DefaultEnvironment>(&contract);
+        let root_key = Key::from([0x42; 32]);
+        let vec = vec_from_slice(b"abcde");
+        SpreadLayout::push_spread(&vec, &mut KeyPtr::from(root_key));
+        // The length and one cell per element.
+        assert_eq!(used_cells()?, 6);
+
+        let mut pulled =
+            <StorageVec<u8> as SpreadLayout>::pull_spread(&mut KeyPtr::from(root_key));
+        pulled.retain(|value| *value != b'a' && *value != b'c');
+        SpreadLayout::push_spread(&pulled, &mut KeyPtr::from(root_key));
+        assert_eq!(used_cells()?, 4);
+        let pulled_again =
+            <StorageVec<u8> as SpreadLayout>::pull_spread(&mut KeyPtr::from(root_key));
+        assert_eq!(pulled_again, vec_from_slice(b"bde"));
+        Ok(())
+    })
+}
+
+#[test]
+fn insert_works() {
+    let mut vec = vec_from_slice(b"bd");
+    vec.insert(0, b'a');
+    assert_eq_slice(&vec, b"abd");
+    vec.insert(3, b'e');
+    assert_eq_slice(&vec, b"abde");
+    vec.insert(2, b'c');
+    assert_eq_slice(&vec, b"abcde");
+    let mut empty = <StorageVec<u8>>::new();
+    empty.insert(0, b'a');
+    assert_eq_slice(&empty, b"a");
+}
+
+#[test]
+#[should_panic(expected = "insertion index (is 3) should be <= len (is 2)")]
+fn insert_out_of_bounds_panics() {
+    let mut vec = vec_from_slice(b"ab");
+    vec.insert(3, b'c');
+}
+
+#[test]
+fn remove_works() {
+    let mut vec = vec_from_slice(b"abcde");
+    assert_eq!(vec.remove(2), b'c');
+    assert_eq_slice(&vec, b"abde");
+    assert_eq!(vec.remove(0), b'a');
+    assert_eq_slice(&vec, b"bde");
+    assert_eq!(vec.remove(2), b'e');
+    assert_eq_slice(&vec, b"bd");
+    assert_eq!(vec.remove(1), b'd');
+    assert_eq!(vec.remove(0), b'b');
+    assert!(vec.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "removal index (is 2) should be < len (is 2)")]
+fn remove_out_of_bounds_panics() {
+    let mut vec = vec_from_slice(b"ab");
+    vec.remove(2);
+}
+
+#[test]
+fn insert_remove_work_on_pulled_vec() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        let root_key = Key::from([0x42; 32]);
+        let vec = vec_from_slice(b"acd");
+        SpreadLayout::push_spread(&vec, &mut KeyPtr::from(root_key));
+        let mut pulled =
+            <StorageVec<u8> as SpreadLayout>::pull_spread(&mut KeyPtr::from(root_key));
+        pulled.insert(1, b'b');
+        assert_eq!(pulled.remove(3), b'd');
+        SpreadLayout::push_spread(&pulled, &mut KeyPtr::from(root_key));
+        let pulled_again =
+            <StorageVec<u8> as SpreadLayout>::pull_spread(&mut KeyPtr::from(root_key));
+        assert_eq!(pulled_again, vec_from_slice(b"abc"));
+        Ok(())
+    })
+}
+
 #[test]
 fn test_binary_search() {
     let b: StorageVec<i32> = StorageVec::new();
@@ -460,7 +573,7 @@ fn test_binary_search() {
     assert_eq!(b.binary_search(&0), Err(0));
     assert_eq!(b.binary_search(&1), Ok(0));
     assert_eq!(b.binary_search(&2), Err(1));
-    matches!(b.binary_search(&3), Ok(1..=3));
+    assert!(matches!(b.binary_search(&3), Ok(1..=3)));
     assert_eq!(b.binary_search(&4), Err(4));
     assert_eq!(b.binary_search(&5), Err(4));
     assert_eq!(b.binary_search(&6), Err(4));
@@ -482,6 +595,39 @@ fn test_binary_search_by_overflow() {
     assert_eq!(b.binary_search_by(|_| Ordering::Less), Err(u8::MAX as u32));
 }
 
+#[test]
+fn test_binary_search_by_edge_cases() {
+    let b: StorageVec<i32> = StorageVec::new();
+    assert_eq!(b.binary_search_by(|probe| probe.cmp(&5)), Err(0));
+
+    let b = vec_from_slice(&[4]);
+    assert_eq!(b.binary_search_by(|probe| probe.cmp(&3)), Err(0));
+    assert_eq!(b.binary_search_by(|probe| probe.cmp(&4)), Ok(0));
+    assert_eq!(b.binary_search_by(|probe| probe.cmp(&5)), Err(1));
+
+    let b = vec_from_slice(&[2, 2, 2, 2]);
+    assert!(matches!(b.binary_search_by(|probe| probe.cmp(&2)), Ok(0..=3)));
+    assert_eq!(b.binary_search_by(|probe| probe.cmp(&1)), Err(0));
+    assert_eq!(b.binary_search_by(|probe| probe.cmp(&3)), Err(4));
+}
+
+#[test]
+fn binary_search_works_on_pulled_vec() -> ink_env::Result<()> {
+    ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+        let root_key = Key::from([0x42; 32]);
+        let vec = vec_from_slice(&[10u64, 20, 20, 30, 40]);
+        SpreadLayout::push_spread(&vec, &mut KeyPtr::from(root_key));
+        // The pulled vector loads its elements lazily while searching.
+        let pulled =
+            <StorageVec<u64> as SpreadLayout>::pull_spread(&mut KeyPtr::from(root_key));
+        assert_eq!(pulled.binary_search(&10), Ok(0));
+        assert!(matches!(pulled.binary_search(&20), Ok(1..=2)));
+        assert_eq!(pulled.binary_search(&25), Err(3));
+        assert_eq!(pulled.binary_search(&50), Err(5));
+        Ok(())
+    })
+}
+
 #[test]
 // Test implementation specific behavior when finding equivalent elements.
 fn test_binary_search_implementation_details() {
@@ -22,6 +22,7 @@ use super::{
 use crate::traits::{
     clear_packed_root,
     pull_packed_root_opt,
+    try_pull_packed_root_opt,
     ExtKeyPtr,
     KeyPtr,
     PackedLayout,
@@ -145,6 +146,85 @@ where
     entry: BTreeMapEntry<'a, K, Box<StorageEntry<V>>>,
 }
 
+/// A pair key given by references to its components.
+///
+/// Owned pair keys borrow as `dyn PairKey` so that cached entries can be
+/// looked up by the components of their key without constructing the owned
+/// pair. Pair keys are ordered lexicographically like tuples.
+pub trait PairKey<A, B> {
+    /// Returns the first component of the pair.
+    fn first(&self) -> &A;
+    /// Returns the second component of the pair.
+    fn second(&self) -> &B;
+}
+
+impl<A, B> PairKey<A, B> for (A, B) {
+    fn first(&self) -> &A {
+        &self.0
+    }
+
+    fn second(&self) -> &B {
+        &self.1
+    }
+}
+
+impl<A, B> PairKey<A, B> for (&A, &B) {
+    fn first(&self) -> &A {
+        self.0
+    }
+
+    fn second(&self) -> &B {
+        self.1
+    }
+}
+
+impl<'a, A, B> Borrow<dyn PairKey<A, B> + 'a> for (A, B)
+where
+    A: 'a,
+    B: 'a,
+{
+    fn borrow(&self) -> &(dyn PairKey<A, B> + 'a) {
+        self
+    }
+}
+
+impl<A, B> PartialEq for dyn PairKey<A, B> + '_
+where
+    A: PartialEq,
+    B: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        (self.first(), self.second()) == (other.first(), other.second())
+    }
+}
+
+impl<A, B> Eq for dyn PairKey<A, B> + '_
+where
+    A: Eq,
+    B: Eq,
+{
+}
+
+impl<A, B> PartialOrd for dyn PairKey<A, B> + '_
+where
+    A: Ord,
+    B: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A, B> Ord for dyn PairKey<A, B> + '_
+where
+    A: Ord,
+    B: Ord,
+{
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.first(), self.second()).cmp(&(other.first(), other.second()))
+    }
+}
+
 /// An entry within the `LazyHashMap`.
 pub enum Entry<'a, K: 'a, V: 'a>
 where
@@ -510,9 +590,13 @@ where
     Key: From<<H as HashOutput>::Type>,
 {
     /// Returns an offset key for the given key pair.
+    ///
+    /// # Note
+    ///
+    /// `key` is not required to be a borrowed form of `K` but must share its
+    /// SCALE encoding, e.g. `(&A, &B)` for a `K` of `(A, B)`.
     fn to_offset_key<Q>(&self, storage_key: &Key, key: &Q) -> Key
     where
-        K: Borrow<Q>,
         Q: scale::Encode,
     {
         #[derive(scale::Encode)]
@@ -534,7 +618,7 @@ where
     }
 
     /// Returns an offset key for the given key.
-    fn key_at<Q>(&self, key: &Q) -> Option<Key>
+    pub(crate) fn key_at<Q>(&self, key: &Q) -> Option<Key>
     where
         K: Borrow<Q>,
         Q: scale::Encode,
@@ -571,6 +655,27 @@ where
     /// within internal use only and should never be given outside the lazy entity
     /// for public `&self` methods.
     unsafe fn lazily_load<Q>(&self, key: &Q) -> NonNull<StorageEntry<V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        self.try_lazily_load(key).unwrap_or_else(|error| {
+            panic!("failed to lazily load a value from storage: {:?}", error)
+        })
+    }
+
+    /// Like [`LazyHashMap::lazily_load`] but returns an error instead of panicking
+    /// if the value cannot be decoded from the contract storage.
+    ///
+    /// Values that failed to load are not cached.
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`LazyHashMap::lazily_load`].
+    unsafe fn try_lazily_load<Q>(
+        &self,
+        key: &Q,
+    ) -> ink_env::Result<NonNull<StorageEntry<V>>>
     where
         K: Borrow<Q>,
         Q: Ord + scale::Encode + ToOwned<Owned = K>,
@@ -591,19 +696,16 @@ where
         // Read more about the issue here: https://github.com/rust-lang/rust/issues/56167
         match cached_entries.entry(key.to_owned()) {
             BTreeMapEntry::Occupied(occupied) => {
-                NonNull::from(&mut **occupied.into_mut())
+                Ok(NonNull::from(&mut **occupied.into_mut()))
             }
             BTreeMapEntry::Vacant(vacant) => {
-                let value = self
-                    .key_at(key)
-                    .map(|key| pull_packed_root_opt::<V>(&key))
-                    .unwrap_or(None);
-                NonNull::from(
-                    &mut **vacant.insert(Box::new(StorageEntry::new(
-                        value,
-                        EntryState::Preserved,
-                    ))),
-                )
+                let value = match self.key_at(key) {
+                    Some(key) => try_pull_packed_root_opt::<V>(&key)?,
+                    None => None,
+                };
+                Ok(NonNull::from(&mut **vacant.insert(Box::new(
+                    StorageEntry::new(value, EntryState::Preserved),
+                ))))
             }
         }
     }
@@ -678,6 +780,23 @@ where
         unsafe { &*self.lazily_load(index).as_ptr() }.value().into()
     }
 
+    /// Returns a shared reference to the value associated with the given key if any.
+    ///
+    /// # Errors
+    ///
+    /// If the value exists in the contract storage but cannot be decoded.
+    pub fn try_get<Q>(&self, index: &Q) -> ink_env::Result<Option<&V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        // SAFETY: Dereferencing the `*mut T` pointer into a `&T` is safe
+        //         since this method's receiver is `&self` so we do not
+        //         leak non-shared references to the outside.
+        let entry = unsafe { &*self.try_lazily_load(index)?.as_ptr() };
+        Ok(entry.value().into())
+    }
+
     /// Returns an exclusive reference to the value associated with the given key if any.
     ///
     /// # Panics
@@ -749,12 +868,140 @@ where
         loaded_y.replace_state(EntryState::Mutated);
         core::mem::swap(loaded_x.value_mut(), loaded_y.value_mut());
     }
+
+    /// Returns exclusive references to the values associated with all given keys.
+    ///
+    /// Returns `None` if any of the keys is given more than once or has no value.
+    ///
+    /// # Panics
+    ///
+    /// - If the lazy hashmap is in an invalid state that forbids interaction.
+    /// - If the decoding of one of the elements failed.
+    pub fn get_many_mut<Q, const N: usize>(
+        &mut self,
+        keys: [&Q; N],
+    ) -> Option<[&mut V; N]>
+    where
+        K: Borrow<Q>,
+        Q: Ord + scale::Encode + ToOwned<Owned = K>,
+    {
+        for (n, key) in keys.iter().enumerate() {
+            if keys[..n].contains(key) {
+                return None
+            }
+        }
+        let mut loaded = [NonNull::<StorageEntry<V>>::dangling(); N];
+        for (entry, key) in loaded.iter_mut().zip(keys) {
+            // SAFETY: No references into the cached entries are alive while
+            //         loading, only the pointers returned by `lazily_load`.
+            *entry = unsafe { self.lazily_load(key) };
+            if unsafe { entry.as_ref() }.value().is_none() {
+                return None
+            }
+        }
+        // SAFETY: The loaded entries are distinct from each other guaranteed
+        //         by the previous check on the keys. Also `lazily_load`
+        //         guarantees to return pointers to pinned entities so that
+        //         loading an entry does not move the entries loaded before it.
+        Some(loaded.map(|entry| {
+            unsafe { &mut *entry.as_ptr() }
+                .value_mut()
+                .as_mut()
+                .expect("the loaded entries have been checked to be occupied")
+        }))
+    }
+}
+
+impl<A, B, V, H> LazyHashMap<(A, B), V, H>
+where
+    A: Ord + scale::Encode + scale::Decode,
+    B: Ord + scale::Encode + scale::Decode,
+    V: PackedLayout,
+    H: CryptoHash,
+    Key: From<<H as HashOutput>::Type>,
+{
+    /// Returns the owned pair key for the given components.
+    ///
+    /// The pair is decoded from the encoded components since `(A, B)` and
+    /// `(&A, &B)` share their SCALE encoding. This does not require the
+    /// components to be `Clone`.
+    fn owned_pair(a: &A, b: &B) -> (A, B) {
+        <(A, B) as scale::Decode>::decode(&mut &scale::Encode::encode(&(a, b))[..])
+            .expect("a pair must decode from the encoding of its components")
+    }
+
+    /// Loads the value of the pair key from the contract storage without caching it.
+    fn pull_pair(&self, a: &A, b: &B) -> Option<V> {
+        let storage_key = self.key.as_ref()?;
+        pull_packed_root_opt::<V>(&self.to_offset_key(storage_key, &(a, b)))
+    }
+
+    /// Returns a shared reference to the value associated with the pair key
+    /// given by its components if any.
+    ///
+    /// # Note
+    ///
+    /// The key is hashed from the borrowed components. The owned pair is only
+    /// constructed once a value is loaded from the contract storage in order
+    /// to cache it. Absent values are not cached.
+    ///
+    /// # Panics
+    ///
+    /// If the decoding of the value failed.
+    pub fn get_pair(&self, a: &A, b: &B) -> Option<&V> {
+        let pair: &dyn PairKey<A, B> = &(a, b);
+        if let Some(entry) = self.entries().get(pair) {
+            return entry.value().into()
+        }
+        let value = self.pull_pair(a, b)?;
+        // SAFETY: The cached entries are boxed so inserting another entry does
+        //         not invalidate references into the entries loaded before,
+        //         see `LazyHashMap::try_lazily_load`.
+        let cached_entries = unsafe { &mut *self.cached_entries.get_ptr().as_ptr() };
+        let entry = cached_entries
+            .entry(Self::owned_pair(a, b))
+            .or_insert_with(|| {
+                Box::new(StorageEntry::new(Some(value), EntryState::Preserved))
+            });
+        entry.value().into()
+    }
+
+    /// Returns `true` if there is a value associated with the pair key given
+    /// by its components.
+    ///
+    /// # Note
+    ///
+    /// See [`LazyHashMap::get_pair`] for when the owned pair is constructed.
+    pub fn contains_pair(&self, a: &A, b: &B) -> bool {
+        self.get_pair(a, b).is_some()
+    }
+
+    /// Removes the value associated with the pair key given by its components
+    /// and returns it if any.
+    ///
+    /// # Note
+    ///
+    /// The owned pair is only constructed if the value has not been cached,
+    /// yet, in order to cache its removal.
+    ///
+    /// # Panics
+    ///
+    /// If the decoding of the value failed.
+    pub fn take_pair(&mut self, a: &A, b: &B) -> Option<V> {
+        let pair: &dyn PairKey<A, B> = &(a, b);
+        if let Some(entry) = self.entries_mut().get_mut(pair) {
+            return entry.put(None)
+        }
+        let value = self.pull_pair(a, b)?;
+        self.put(Self::owned_pair(a, b), None);
+        Some(value)
+    }
 }
 
 impl<'a, K, V> Entry<'a, K, V>
 where
     K: Ord + Clone + PackedLayout,
-    V: PackedLayout + core::fmt::Debug + core::cmp::Eq + Default,
+    V: PackedLayout,
 {
     /// Returns a reference to this entry's key.
     pub fn key(&self) -> &K {
@@ -766,7 +1013,10 @@ where
 
     /// Ensures a value is in the entry by inserting the default value if empty, and returns
     /// a reference to the value in the entry.
-    pub fn or_default(self) -> &'a V {
+    pub fn or_default(self) -> &'a V
+    where
+        V: Default,
+    {
         match self {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => entry.insert(V::default()),
@@ -1358,4 +1608,41 @@ mod tests {
             Ok(())
         })
     }
+
+    /// A pair key component that deliberately does not implement `Clone`.
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, scale::Encode, scale::Decode)]
+    struct Owner(u8);
+
+    type PairHashMap = LazyHashMap<(Owner, u32), u8, Blake2x256>;
+
+    #[test]
+    fn pair_lookups_work_without_clone() -> ink_env::Result<()> {
+        ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+            let mut hmap = PairHashMap::new();
+            hmap.put((Owner(1), 10), Some(b'A'));
+            hmap.put((Owner(1), 20), Some(b'B'));
+            assert_eq!(hmap.get_pair(&Owner(1), &10), Some(&b'A'));
+            assert!(!hmap.contains_pair(&Owner(2), &10));
+            let root_key = Key::from([0x42; 32]);
+            SpreadLayout::push_spread(&hmap, &mut KeyPtr::from(root_key));
+
+            // The values are loaded from the contract storage by the hash of
+            // the borrowed pair.
+            let mut hmap2 =
+                <PairHashMap as SpreadLayout>::pull_spread(&mut KeyPtr::from(root_key));
+            assert_eq!(hmap2.get_pair(&Owner(1), &20), Some(&b'B'));
+            assert_eq!(hmap2.get_pair(&Owner(1), &30), None);
+            assert_eq!(hmap2.take_pair(&Owner(1), &10), Some(b'A'));
+            assert_eq!(hmap2.take_pair(&Owner(1), &10), None);
+            assert!(hmap2.contains_pair(&Owner(1), &20));
+
+            // The removal is cleared from the contract storage once pushed.
+            SpreadLayout::push_spread(&hmap2, &mut KeyPtr::from(root_key));
+            let hmap3 =
+                <PairHashMap as SpreadLayout>::pull_spread(&mut KeyPtr::from(root_key));
+            assert_eq!(hmap3.get_pair(&Owner(1), &10), None);
+            assert_eq!(hmap3.get_pair(&Owner(1), &20), Some(&b'B'));
+            Ok(())
+        })
+    }
 }
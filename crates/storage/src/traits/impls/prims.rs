@@ -20,6 +20,18 @@ use crate::traits::{
     SpreadAllocate,
     SpreadLayout,
 };
+use core::num::{
+    NonZeroI128,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI8,
+    NonZeroU128,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU8,
+};
 use ink_env::{
     AccountId,
     Hash,
@@ -61,6 +73,54 @@ impl_layout_for_primitive!(
     i8, i16, i32, i64, i128,
 );
 
+macro_rules! impl_layout_for_non_zero {
+    ( $($ty:ty),* $(,)? ) => {
+        $(
+            // There are no `SpreadAllocate` and `PackedAllocate` implementations
+            // since non-zero integers do not have a default value.
+            impl SpreadLayout for $ty {
+                const FOOTPRINT: u64 = 1;
+                const REQUIRES_DEEP_CLEAN_UP: bool = false;
+
+                #[inline]
+                fn pull_spread(ptr: &mut KeyPtr) -> Self {
+                    super::forward_pull_packed::<Self>(ptr)
+                }
+
+                #[inline]
+                fn push_spread(&self, ptr: &mut KeyPtr) {
+                    super::forward_push_packed::<Self>(self, ptr)
+                }
+
+                #[inline]
+                fn clear_spread(&self, ptr: &mut KeyPtr) {
+                    super::forward_clear_packed::<Self>(self, ptr)
+                }
+            }
+            impl PackedLayout for $ty {
+                #[inline]
+                fn pull_packed(&mut self, _at: &Key) {}
+                #[inline]
+                fn push_packed(&self, _at: &Key) {}
+                #[inline]
+                fn clear_packed(&self, _at: &Key) {}
+            }
+        )*
+    };
+}
+impl_layout_for_non_zero!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+);
+
 impl<T> SpreadLayout for Option<T>
 where
     T: SpreadLayout,
@@ -291,10 +351,15 @@ where
 #[cfg(test)]
 mod tests {
     use crate::push_pull_works_for_primitive;
+    use core::num::NonZeroU128;
     use ink_env::AccountId;
     use ink_primitives::Key;
 
     push_pull_works_for_primitive!(bool, [false, true]);
+    push_pull_works_for_primitive!(
+        NonZeroU128,
+        [NonZeroU128::new(1).unwrap(), NonZeroU128::new(u128::MAX).unwrap()]
+    );
     push_pull_works_for_primitive!(
         String,
         [Default::default(), String::from("Hello, World!")]
@@ -18,6 +18,18 @@ use crate::traits::{
     KeyPtr,
     SpreadLayout,
 };
+use core::num::{
+    NonZeroI128,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI8,
+    NonZeroU128,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU8,
+};
 use ink_env::{
     AccountId,
     Hash,
@@ -58,6 +70,8 @@ impl_storage_layout_for_primitives!(
     bool, char, (),
     u8, u16, u32, u64, u128,
     i8, i16, i32, i64, i128,
+    NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128,
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128,
 );
 
 macro_rules! impl_storage_layout_for_arrays {
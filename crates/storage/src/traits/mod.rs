@@ -40,6 +40,7 @@ pub use self::layout::{
 pub(crate) use self::optspec::{
     clear_spread_root_opt,
     pull_packed_root_opt,
+    try_pull_packed_root_opt,
     pull_spread_root_opt,
     push_packed_root_opt,
     push_spread_root_opt,
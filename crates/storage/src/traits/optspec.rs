@@ -102,19 +102,28 @@ pub fn pull_packed_root_opt<T>(root_key: &Key) -> Option<T>
 where
     T: PackedLayout,
 {
-    ink_env::get_contract_storage::<T>(root_key)
-        .unwrap_or_else(|error| {
-            panic!(
-                "failed to pull packed from root key {}: {:?}",
-                root_key, error
-            )
-        })
-        .map(|mut value| {
+    try_pull_packed_root_opt::<T>(root_key).unwrap_or_else(|error| {
+        panic!(
+            "failed to pull packed from root key {}: {:?}",
+            root_key, error
+        )
+    })
+}
+
+/// Like [`pull_packed_root_opt`] but returns an error instead of panicking
+/// if the contract storage at `root_key` cannot be decoded.
+pub fn try_pull_packed_root_opt<T>(root_key: &Key) -> ink_env::Result<Option<T>>
+where
+    T: PackedLayout,
+{
+    ink_env::get_contract_storage::<T>(root_key).map(|value| {
+        value.map(|mut value| {
             // In case the contract storage is occupied at the root key
             // we handle the Option<T> as if it was a T.
             <T as PackedLayout>::pull_packed(&mut value, root_key);
             value
         })
+    })
 }
 
 pub fn push_packed_root_opt<T>(entity: Option<&T>, root_key: &Key)
@@ -2,10 +2,49 @@
 
 use ink_lang as ink;
 
+#[cfg(feature = "std")]
+pub use self::erc20::format_amount;
+
 #[ink::contract]
 mod erc20 {
+    use ink_prelude::vec::Vec;
     // #[cfg(not(feature = "ink-as-dependency"))]
-    use ink_storage::collections::HashMap;
+    use ink_storage::{
+        collections::{
+            BinaryHeap,
+            HashMap,
+            Vec as StorageVec,
+        },
+        traits::{
+            PackedLayout,
+            SpreadLayout,
+        },
+    };
+
+    /// The maximum number of transfers that can be batched into a single call.
+    pub const MAX_BATCH_TRANSFERS: usize = 32;
+
+    /// The selector of the `on_token_received(from, value, data)` message that
+    /// is invoked on the recipient of `transfer_and_call`.
+    ///
+    /// Equals the default selector of an ink! message named `on_token_received`.
+    #[cfg_attr(test, allow(dead_code))]
+    pub const ON_TOKEN_RECEIVED_SELECTOR: [u8; 4] = [0x41, 0xEB, 0x77, 0x88];
+
+    /// The factor by which the accumulated reward per token is scaled in order
+    /// to keep the rounding error of small reward deposits low.
+    pub const REWARD_PRECISION: Balance = 1_000_000_000_000;
+
+    /// Stands in for the recipient's `on_token_received` in unit tests since
+    /// the off-chain environment does not support calling other contracts.
+    #[cfg(test)]
+    type TokenReceivedHook = fn(&mut Erc20, AccountId, AccountId, Balance) -> bool;
+
+    #[cfg(test)]
+    std::thread_local! {
+        static TOKEN_RECEIVED_HOOK: core::cell::Cell<Option<TokenReceivedHook>> =
+            core::cell::Cell::new(None);
+    }
 
     /// Defines the storage of your contract.
     /// Add new fields to the below struct in order
@@ -19,6 +58,81 @@ mod erc20 {
 
         /// Balances that are spendable by non-owners: (owner, spender) -> allowed
         allowances: HashMap<(AccountId, AccountId), Balance>,
+
+        /// The point in time at which an allowance expires: (owner, spender) -> expires_at
+        ///
+        /// Allowances without an entry never expire.
+        allowance_expiries: HashMap<(AccountId, AccountId), Timestamp>,
+
+        /// Every expiry set by `approve_until`, the earliest one on top.
+        ///
+        /// Entries are not removed if an allowance is changed afterwards, hence
+        /// an entry is stale unless it matches `allowance_expiries`.
+        expiry_queue: BinaryHeap<QueuedExpiry>,
+
+        /// The account that deployed the contract and may manage its settings.
+        owner: AccountId,
+
+        /// Accounts whose balances are excluded from the circulating supply,
+        /// e.g. a treasury or a burn address.
+        non_circulating: StorageVec<AccountId>,
+
+        /// The id of the most recent snapshot, `0` if no snapshot has been taken.
+        current_snapshot_id: u32,
+
+        /// Balances recorded for snapshots: (snapshot_id, account) -> balance
+        ///
+        /// A balance is only recorded upon the first change of an account's
+        /// balance after a snapshot has been taken.
+        snapshot_balances: HashMap<(u32, AccountId), Balance>,
+
+        /// The id of the latest snapshot for which an account's balance has been recorded.
+        last_snapshot_update: HashMap<AccountId, u32>,
+
+        /// The transfer rate limit as (window, limit) if enabled.
+        ///
+        /// An account may transfer at most `limit` tokens per `window`.
+        rate_limit: Option<(Timestamp, Balance)>,
+
+        /// The start of the current window and the amount transferred within it per account.
+        transfer_windows: HashMap<AccountId, (Timestamp, Balance)>,
+
+        /// Whether a non-zero allowance must be reset to zero before it can be changed.
+        strict_approve: bool,
+
+        /// Whether absent `Transfer` parties are emitted as the zero account topic.
+        zero_address_topics: bool,
+
+        /// Whether a `transfer_and_call` is currently invoking its recipient.
+        entered: bool,
+
+        /// The maximum total supply that may be reached by minting if capped.
+        cap: Option<Balance>,
+
+        /// The maximum amount of a single transfer if limited.
+        max_transfer: Option<Balance>,
+
+        /// The rewards deposited per token so far, scaled by `REWARD_PRECISION`.
+        reward_per_token_stored: Balance,
+
+        /// The `reward_per_token_stored` up to which the rewards of an account
+        /// have been accounted to `rewards`.
+        reward_per_token_paid: HashMap<AccountId, Balance>,
+
+        /// The accounted but not yet claimed rewards per account.
+        rewards: HashMap<AccountId, Balance>,
+
+        /// Whether moving tokens between accounts is paused.
+        transfers_paused: bool,
+
+        /// Whether minting is paused.
+        mint_paused: bool,
+
+        /// Whether burning is paused.
+        burn_paused: bool,
+
+        /// Accounts that may not send tokens until they are unfrozen.
+        frozen: HashMap<AccountId, ()>,
     }
 
     /// Defines the event of your contract
@@ -40,6 +154,21 @@ mod erc20 {
         value: Balance,
     }
 
+    /// Event emitted when an expired allowance is pruned.
+    #[ink(event)]
+    pub struct AllowanceExpired {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+    }
+
+    /// Event emitted when a snapshot of the balances is taken.
+    #[ink(event)]
+    pub struct Snapshot {
+        id: u32,
+    }
+
     // PartialEq, 否则Error间无法比较 (==)
     // Debug, 否则无法assert进行debug
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -47,33 +176,185 @@ mod erc20 {
     pub enum Error {
         InsufficientBalance,
         InsufficientAllowance,
+        /// Returned if the caller is not the contract owner.
+        NotOwner,
+        /// Returned if the account is already marked as non-circulating.
+        AlreadyNonCirculating,
+        /// Returned if the account is not marked as non-circulating.
+        NotNonCirculating,
+        /// Returned if the snapshot id does not refer to a taken snapshot.
+        InvalidSnapshot,
+        /// Returned if a transfer exceeds the rate limit of the current window.
+        RateLimited,
+        /// Returned if a non-zero allowance is changed to another non-zero value
+        /// in strict approval mode.
+        UnsafeApproval,
+        /// Returned if a batch holds more than `MAX_BATCH_TRANSFERS` transfers.
+        BatchTooLarge,
+        /// Returned if increasing an allowance would overflow.
+        AllowanceOverflow,
+        /// Returned if tokens are moved while the recipient of a
+        /// `transfer_and_call` is invoked.
+        ReentrantCall,
+        /// Returned if the recipient of a `transfer_and_call` rejected the tokens.
+        TransferRejected,
+        /// Returned if minting would exceed the supply cap or overflow the total supply.
+        CapExceeded,
+        /// Returned if an account is listed more than once in a migration.
+        DuplicateAccount,
+        /// Returned if a single transfer exceeds the maximum transfer amount.
+        TransferExceedsMax,
+        /// Returned if rewards are deposited while no tokens are held outside of
+        /// the reward pool.
+        NoRewardRecipients,
+        /// Returned if depositing rewards would overflow the reward per token or
+        /// if the rewards accrued by an account overflow.
+        RewardOverflow,
+        /// Returned if the reward pool does not hold the rewards to be claimed.
+        InsufficientRewardPool,
+        /// Returned if the requested operation is currently paused.
+        Paused,
+        /// Returned if tokens are sent from a frozen account.
+        AccountFrozen,
+        /// Returned if the account is not frozen.
+        NotFrozen,
     }
 
     type Result<T> = core::result::Result<T, Error>;
 
+    /// An allowance expiry queued for pruning.
+    ///
+    /// Ordered in reverse by the point in time of the expiry so that the
+    /// earliest expiry is popped first from the max-heap of queued expiries.
+    #[derive(
+        scale::Encode,
+        scale::Decode,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct QueuedExpiry {
+        expires_at: Timestamp,
+        owner: AccountId,
+        spender: AccountId,
+    }
+
+    impl Ord for QueuedExpiry {
+        fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+            (other.expires_at, other.owner, other.spender).cmp(&(
+                self.expires_at,
+                self.owner,
+                self.spender,
+            ))
+        }
+    }
+
+    impl PartialOrd for QueuedExpiry {
+        fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    /// Marks the contract as entered while the recipient of a `transfer_and_call`
+    /// is invoked and clears the mark once dropped, also if the recipient panics.
+    struct Entered<'a> {
+        contract: &'a mut Erc20,
+    }
+
+    impl<'a> Entered<'a> {
+        fn new(contract: &'a mut Erc20) -> Self {
+            contract.entered = true;
+            Self { contract }
+        }
+    }
+
+    impl Drop for Entered<'_> {
+        fn drop(&mut self) {
+            self.contract.entered = false;
+        }
+    }
+
     impl Erc20 {
         /// Constructor that initializes the `bool` value to the given `init_value`.
         #[ink(constructor)]
         pub fn new(init_supply: Balance) -> Self {
+            Self::new_with_strict_approve(init_supply, false)
+        }
+
+        /// Constructor that additionally configures whether approvals are strict.
+        ///
+        /// With `strict_approve` a non-zero allowance has to be reset to zero
+        /// before it can be changed to another non-zero value.
+        #[ink(constructor)]
+        pub fn new_with_strict_approve(init_supply: Balance, strict_approve: bool) -> Self {
+            Self::new_init(init_supply, strict_approve, false)
+        }
+
+        /// Constructor that additionally configures how mints and burns are emitted.
+        ///
+        /// With `zero_address_topics` the absent `from` of a mint and the absent
+        /// `to` of a burn are emitted as the zero `AccountId` instead of `None`
+        /// in order to match EVM-style indexers.
+        #[ink(constructor)]
+        pub fn new_with_zero_address_topics(
+            init_supply: Balance,
+            zero_address_topics: bool,
+        ) -> Self {
+            Self::new_init(init_supply, false, zero_address_topics)
+        }
+
+        fn new_init(
+            init_supply: Balance,
+            strict_approve: bool,
+            zero_address_topics: bool,
+        ) -> Self {
             let caller = Self::env().caller();
             let allowances = HashMap::new();
             let mut balances = HashMap::new();
             balances.insert(caller, init_supply);
 
+            let instance = Self {
+                total_supply: init_supply,
+                balances,
+                allowances,
+                allowance_expiries: HashMap::new(),
+                expiry_queue: BinaryHeap::new(),
+                owner: caller,
+                non_circulating: StorageVec::new(),
+                current_snapshot_id: 0,
+                snapshot_balances: HashMap::new(),
+                last_snapshot_update: HashMap::new(),
+                rate_limit: None,
+                transfer_windows: HashMap::new(),
+                strict_approve,
+                zero_address_topics,
+                entered: false,
+                cap: None,
+                max_transfer: None,
+                reward_per_token_stored: 0,
+                reward_per_token_paid: HashMap::new(),
+                rewards: HashMap::new(),
+                transfers_paused: false,
+                mint_paused: false,
+                burn_paused: false,
+                frozen: HashMap::new(),
+            };
             Self::env()
                 .emit_event(
                     Transfer {
-                        from: None,
+                        from: instance.transfer_party(None),
                         to: Some(caller),
                         value: init_supply,
                     }
                 );
-
-            Self {
-                total_supply: init_supply,
-                balances,
-                allowances
-            }
+            instance
         }
 
         /// Constructor that initializes the `bool` value to `false`.
@@ -92,199 +373,2513 @@ mod erc20 {
             self.total_supply
         }
 
-        /// check the balance of the owner
+        /// Returns the total supply minus the balances of all non-circulating accounts.
         #[ink(message)]
-        pub fn balance_of(&self, owner: AccountId) -> Balance {
-            // ACTION: Return the balance of `owner`
-            Self::balance_of_or_zero(&self, &owner)
+        pub fn circulating_supply(&self) -> Balance {
+            let non_circulating = self
+                .non_circulating
+                .iter()
+                .map(|account| self.balance_of_or_zero(account))
+                .try_fold(0, |acc: Balance, balance| acc.checked_add(balance))
+                .expect("non-circulating balances cannot overflow the total supply");
+            self.total_supply
+                .checked_sub(non_circulating)
+                .expect("non-circulating balances cannot exceed the total supply")
         }
 
-        fn balance_of_or_zero(&self, owner: &AccountId) -> Balance {
-            // ACTION: `get` the balance of `owner`, then `unwrap_or` fallback to 0
-            *self.balances.get(owner).unwrap_or(&0)
+        /// Marks `account` as non-circulating.
+        ///
+        /// Only the contract owner may call this.
+        #[ink(message)]
+        pub fn add_non_circulating(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            if self.non_circulating.iter().any(|a| a == &account) {
+                return Err(Error::AlreadyNonCirculating)
+            }
+            self.non_circulating.push(account);
+            Ok(())
         }
 
-        /// Approve the passed AccountId to spend the specified amount of tokens
-        /// on the behalf of the message's sender.
+        /// Removes `account` from the set of non-circulating accounts.
+        ///
+        /// Only the contract owner may call this.
         #[ink(message)]
-        pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
-            // ACTION: Get the `self.env().caller()` and store it as the `owner`
-            let owner = self.env().caller();
+        pub fn remove_non_circulating(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            let index = self
+                .non_circulating
+                .iter()
+                .position(|a| a == &account)
+                .ok_or(Error::NotNonCirculating)?;
+            self.non_circulating.swap_remove_drop(index as u32);
+            Ok(())
+        }
 
-            // ACTION: Insert the new allowance into the `allowances` HashMap
-            self.allowances.insert((owner, spender), value);
+        /// Takes a snapshot of all balances and returns its id.
+        ///
+        /// Only the contract owner may call this.
+        ///
+        /// # Note
+        ///
+        /// Balances are not copied when the snapshot is taken. Instead an
+        /// account's balance is recorded upon its first change afterwards.
+        #[ink(message)]
+        pub fn snapshot(&mut self) -> Result<u32> {
+            self.ensure_owner()?;
+            self.current_snapshot_id += 1;
+            let id = self.current_snapshot_id;
+            self.env().emit_event(Snapshot { id });
+            Ok(id)
+        }
 
-            // ACTION: `emit` the `Approval` event you created using these values
-            self.env()
-                .emit_event(
-                    Approval {
-                        owner: Some(owner),
-                        spender: Some(spender),
-                        value,
-                    }
-                );
+        /// Returns the balance of `account` at the time the snapshot `snapshot_id` was taken.
+        #[ink(message)]
+        pub fn balance_of_at(&self, account: AccountId, snapshot_id: u32) -> Result<Balance> {
+            if snapshot_id == 0 || snapshot_id > self.current_snapshot_id {
+                return Err(Error::InvalidSnapshot)
+            }
+            // The balance at `snapshot_id` is the one recorded for the first
+            // later snapshot upon which the balance changed. If there is none
+            // the balance has not changed since.
+            let balance = (snapshot_id..=self.current_snapshot_id)
+                .find_map(|id| self.snapshot_balances.get(&(id, account)).copied())
+                .unwrap_or_else(|| self.balance_of_or_zero(&account));
+            Ok(balance)
+        }
 
-            Ok(())
+        /// Records the balance of `account` for the current snapshot if this
+        /// has not been done yet.
+        ///
+        /// Must be called before the balance of `account` is changed.
+        fn update_snapshot(&mut self, account: &AccountId) {
+            let current = self.current_snapshot_id;
+            if current == 0 {
+                return
+            }
+            let last = self.last_snapshot_update.get(account).copied().unwrap_or(0);
+            if last < current {
+                let balance = self.balance_of_or_zero(account);
+                self.snapshot_balances.insert((current, *account), balance);
+                self.last_snapshot_update.insert(*account, current);
+            }
         }
 
+        /// Deposits `amount` tokens of the caller as rewards for all token holders.
+        ///
+        /// The tokens are moved into the reward pool held by the contract account
+        /// and distributed proportionally to the balances of all other accounts,
+        /// which may claim them with `claim_rewards`. Holders are not iterated;
+        /// instead the reward per token is accumulated and every account is
+        /// checkpointed whenever its balance changes.
         #[ink(message)]
-        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
-            // ACTION: Create a getter for the `allowances` HashMap
-            Self::allowance_of_or_zero(self, &owner, &spender)
+        pub fn deposit_rewards(&mut self, amount: Balance) -> Result<()> {
+            if self.entered {
+                return Err(Error::ReentrantCall)
+            }
+            self.ensure_transfers_not_paused()?;
+            let caller = self.env().caller();
+            self.ensure_not_frozen(&caller)?;
+            if self.balance_of_or_zero(&caller) < amount {
+                return Err(Error::InsufficientBalance)
+            }
+            let pool = self.env().account_id();
+            let pooled = self
+                .balance_of_or_zero(&pool)
+                .checked_add(amount)
+                .ok_or(Error::RewardOverflow)?;
+            let eligible_supply = self
+                .total_supply
+                .checked_sub(pooled)
+                .filter(|eligible_supply| *eligible_supply != 0)
+                .ok_or(Error::NoRewardRecipients)?;
+            let reward_per_token = amount
+                .checked_mul(REWARD_PRECISION)
+                .map(|scaled| scaled / eligible_supply)
+                .and_then(|delta| self.reward_per_token_stored.checked_add(delta))
+                .ok_or(Error::RewardOverflow)?;
+            self.apply_transfer(caller, pool, amount)?;
+            self.reward_per_token_stored = reward_per_token;
+            Ok(())
         }
 
-        fn allowance_of_or_zero(&self, owner: &AccountId, spender: &AccountId) -> Balance {
-            // ACTION: `get` the `allowances` of `(owner, spender)` and `unwrap_or` return `0`.
-            *self.allowances.get(&(*owner, *spender)).unwrap_or(&0)
+        /// Pays out the rewards accrued by the caller and returns their amount.
+        #[ink(message)]
+        pub fn claim_rewards(&mut self) -> Result<Balance> {
+            if self.entered {
+                return Err(Error::ReentrantCall)
+            }
+            self.ensure_transfers_not_paused()?;
+            let caller = self.env().caller();
+            self.update_reward(&caller)?;
+            let reward = self.rewards.get(&caller).copied().unwrap_or(0);
+            if reward != 0 {
+                let pool = self.env().account_id();
+                if self.balance_of_or_zero(&pool) < reward {
+                    return Err(Error::InsufficientRewardPool)
+                }
+                self.apply_transfer(pool, caller, reward)?;
+                self.rewards.take(&caller);
+            }
+            Ok(reward)
         }
 
+        /// Returns the rewards accrued by `account` that have not been claimed yet.
+        ///
+        /// Returns `Error::RewardOverflow` if the rewards do not fit a `Balance`.
         #[ink(message)]
-        pub fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
-            // ACTION: Get the allowance for `(from, self.env().caller())` using `allowance_of_or_zero`
-            let allowance = Self::allowance_of_or_zero(self, &from, &self.env().caller());
+        pub fn rewards_of(&self, account: AccountId) -> Result<Balance> {
+            let accounted = self.rewards.get(&account).copied().unwrap_or(0);
+            accounted
+                .checked_add(self.pending_reward(&account)?)
+                .ok_or(Error::RewardOverflow)
+        }
 
-            // ACTION: `if` the `allowance` is less than the `value`, exit early and return `false`
-            if allowance < value {
-                return Err(Error::InsufficientAllowance);
+        /// Returns the rewards of `account` accrued since its last checkpoint.
+        ///
+        /// Returns `Error::RewardOverflow` if the rewards do not fit a `Balance`.
+        fn pending_reward(&self, account: &AccountId) -> Result<Balance> {
+            if *account == self.env().account_id() {
+                return Ok(0)
             }
+            let paid = self.reward_per_token_paid.get(account).copied().unwrap_or(0);
+            let balance = self.balance_of_or_zero(account);
+            let per_token = self.reward_per_token_stored - paid;
+            Self::scale_reward(balance, per_token).ok_or(Error::RewardOverflow)
+        }
 
-            // ACTION: `insert` the new allowance into the map for `(from, self.env().caller())`
-            self.allowances.insert((from, self.env().caller()), allowance - value);
+        /// Returns `balance * per_token / REWARD_PRECISION` or `None` if it overflows.
+        ///
+        /// Computed from the quotients and remainders of both factors so that
+        /// the product of the factors does not overflow unless the result does.
+        /// The product of the remainders is below `REWARD_PRECISION^2` and cannot
+        /// overflow.
+        fn scale_reward(balance: Balance, per_token: Balance) -> Option<Balance> {
+            let (balance_high, balance_low) =
+                (balance / REWARD_PRECISION, balance % REWARD_PRECISION);
+            let (per_token_high, per_token_low) =
+                (per_token / REWARD_PRECISION, per_token % REWARD_PRECISION);
+            balance_high
+                .checked_mul(per_token_high)?
+                .checked_mul(REWARD_PRECISION)?
+                .checked_add(balance_high.checked_mul(per_token_low)?)?
+                .checked_add(balance_low.checked_mul(per_token_high)?)?
+                .checked_add(balance_low * per_token_low / REWARD_PRECISION)
+        }
 
-            // ACTION: Finally, call the `transfer_from_to` for `from` and `to`\
-            // ACTION: Return true if everything was successful
-            Self::transfer_from_to(self, from, to, value)
+        /// Accounts the pending rewards of `account` and checkpoints it.
+        ///
+        /// Must be called before the balance of `account` is changed. Leaves the
+        /// state untouched if it returns `Error::RewardOverflow`.
+        fn update_reward(&mut self, account: &AccountId) -> Result<()> {
+            // The reward pool does not earn rewards itself.
+            if *account == self.env().account_id() {
+                return Ok(())
+            }
+            let pending = self.pending_reward(account)?;
+            if pending != 0 {
+                let accounted = self.rewards.get(account).copied().unwrap_or(0);
+                let accounted =
+                    accounted.checked_add(pending).ok_or(Error::RewardOverflow)?;
+                self.rewards.insert(*account, accounted);
+            }
+            self.reward_per_token_paid
+                .insert(*account, self.reward_per_token_stored);
+            Ok(())
         }
 
-        /// transfer the balance from sender to receiver
+        /// Limits the amount every account may transfer to `limit` per `window`.
+        ///
+        /// Only the contract owner may call this.
         #[ink(message)]
-        pub fn transfer(&mut self, to: AccountId, value: Balance) -> Result<()> {
-            // ACTION: Call the `transfer_from_to` with `from` as `self.env().caller()`
-            Self::transfer_from_to(self,self.env().caller(), to, value)
+        pub fn set_rate_limit(&mut self, window: Timestamp, limit: Balance) -> Result<()> {
+            self.ensure_owner()?;
+            self.rate_limit = Some((window, limit));
+            Ok(())
         }
 
-        fn transfer_from_to(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
-            // ACTION: Get the balance for `from` and `to`
-            let balance_from = Self::balance_of_or_zero(&self, &from);
-            let balance_to = Self::balance_of_or_zero(&self, &to);
+        /// Returns the maximum total supply that may be reached by minting if capped.
+        #[ink(message)]
+        pub fn cap(&self) -> Option<Balance> {
+            self.cap
+        }
 
-            // ACTION: If `from_balance` is less than `value`, return `false`
-            if balance_from < value {
-                return Err(Error::InsufficientBalance);
+        /// Caps the total supply that may be reached by minting, `None` removes the cap.
+        ///
+        /// Only the contract owner may call this. Returns `Error::CapExceeded`
+        /// if the total supply already exceeds the new cap.
+        #[ink(message)]
+        pub fn set_cap(&mut self, cap: Option<Balance>) -> Result<()> {
+            self.ensure_owner()?;
+            if matches!(cap, Some(cap) if cap < self.total_supply) {
+                return Err(Error::CapExceeded)
             }
-
-            // ACTION: Insert new values for `from` and `to`
-            //         * from_balance - value
-            self.balances.insert(from, balance_from - value);
-            self.balances.insert(to, balance_to + value);
-
-            self.env()
-                .emit_event(
-                    Transfer {
-                        from: Some(from),
-                        to: Some(to),
-                        value,
-                    }
-                );
-
+            self.cap = cap;
             Ok(())
         }
-    }
 
-    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
-    /// module and test functions are marked with a `#[test]` attribute.
-    /// The below code is technically just normal Rust code.
-    #[cfg(feature = "ink-experimental-engine")]
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+        /// Returns the maximum amount of a single transfer if limited.
+        #[ink(message)]
+        pub fn max_transfer(&self) -> Option<Balance> {
+            self.max_transfer
+        }
 
-        use ink_lang as ink;
+        /// Limits the amount of every single transfer, `None` removes the limit.
+        ///
+        /// Only the contract owner may call this. In contrast to the rate limit
+        /// this bounds each transfer on its own instead of the transfers per window.
+        #[ink(message)]
+        pub fn set_max_transfer(&mut self, limit: Option<Balance>) -> Result<()> {
+            self.ensure_owner()?;
+            self.max_transfer = limit;
+            Ok(())
+        }
 
-        #[ink::test]
-        fn new_works() {
-            let contract = Erc20::new(777);
-            assert_eq!(contract.total_supply(), 777);
+        /// Returns whether moving tokens between accounts is paused.
+        #[ink(message)]
+        pub fn transfers_paused(&self) -> bool {
+            self.transfers_paused
         }
 
-        // the default address is AccountId::from([0x1; 32])
-        #[ink::test]
-        fn balance_works() {
-            let contract = Erc20::new(100);
-            assert_eq!(contract.total_supply(), 100);
-            assert_eq!(contract.balance_of(AccountId::from([0x1; 32])), 100);
-            assert_eq!(contract.balance_of(AccountId::from([0x0; 32])), 0);
+        /// Pauses or resumes moving tokens between accounts.
+        ///
+        /// Only the contract owner may call this. Besides transfers this also
+        /// covers depositing and claiming rewards.
+        #[ink(message)]
+        pub fn set_transfers_paused(&mut self, paused: bool) -> Result<()> {
+            self.ensure_owner()?;
+            self.transfers_paused = paused;
+            Ok(())
         }
 
-        #[ink::test]
-        fn transfer_works() {
-            let mut contract = Erc20::new(100);
-            assert_eq!(contract.total_supply(), 100);
-            contract.transfer(AccountId::from([0x2; 32]), 20);
+        /// Returns whether minting is paused.
+        #[ink(message)]
+        pub fn mint_paused(&self) -> bool {
+            self.mint_paused
+        }
 
-            assert_eq!(contract.balance_of(AccountId::from([0x1; 32])), 80);
-            assert_eq!(contract.balance_of(AccountId::from([0x2; 32])), 20);
+        /// Pauses or resumes minting.
+        ///
+        /// Only the contract owner may call this.
+        #[ink(message)]
+        pub fn set_mint_paused(&mut self, paused: bool) -> Result<()> {
+            self.ensure_owner()?;
+            self.mint_paused = paused;
+            Ok(())
         }
 
-        #[ink::test]
-        fn transfer_insufficient() {
-            let mut contract = Erc20::new(100);
+        /// Returns whether burning is paused.
+        #[ink(message)]
+        pub fn burn_paused(&self) -> bool {
+            self.burn_paused
+        }
 
-            assert_eq!(contract.total_supply(), 100);
-            assert_eq!(contract.balance_of(AccountId::from([0x1; 32])), 100);
+        /// Pauses or resumes burning.
+        ///
+        /// Only the contract owner may call this.
+        #[ink(message)]
+        pub fn set_burn_paused(&mut self, paused: bool) -> Result<()> {
+            self.ensure_owner()?;
+            self.burn_paused = paused;
+            Ok(())
+        }
 
-            assert_eq!(
-                contract.transfer(AccountId::from([0x2; 32]), 120),
-                Err(Error::InsufficientBalance)
-            );
+        /// Returns `Error::Paused` if moving tokens between accounts is paused.
+        fn ensure_transfers_not_paused(&self) -> Result<()> {
+            if self.transfers_paused {
+                return Err(Error::Paused)
+            }
+            Ok(())
         }
 
-        #[ink::test]
-        fn allowance_works() {
-            let mut contract = Erc20::new(100);
-            assert_eq!(contract.total_supply(), 100);
+        /// Returns whether `account` is frozen.
+        #[ink(message)]
+        pub fn is_frozen(&self, account: AccountId) -> bool {
+            self.frozen.contains_key(&account)
+        }
 
-            contract.approve(AccountId::from([0x2; 32]), 20);
+        /// Freezes `account` so that it can no longer send tokens.
+        ///
+        /// Only the contract owner may call this. The tokens of a frozen account
+        /// can still be received and be clawed back by the owner.
+        #[ink(message)]
+        pub fn freeze(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            self.frozen.insert(account, ());
+            Ok(())
+        }
 
-            assert_eq!(contract.allowance(
-                AccountId::from([0x1; 32]),
-                AccountId::from([0x2; 32])
-            ), 20);
+        /// Unfreezes `account`.
+        ///
+        /// Only the contract owner may call this.
+        #[ink(message)]
+        pub fn unfreeze(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            self.frozen.take(&account).ok_or(Error::NotFrozen)
         }
 
-        #[ink::test]
-        fn transfer_from_works() {
-            let mut contract = Erc20::new(100);
+        /// Moves `value` tokens from the frozen account `from` to `to`.
+        ///
+        /// Only the contract owner may call this. No allowance is required, but
+        /// `from` must hold at least `value` tokens. To bound this power only
+        /// frozen accounts may be clawed back from.
+        #[ink(message)]
+        pub fn clawback(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            if self.entered {
+                return Err(Error::ReentrantCall)
+            }
+            self.ensure_owner()?;
+            if !self.is_frozen(from) {
+                return Err(Error::NotFrozen)
+            }
+            if self.balance_of_or_zero(&from) < value {
+                return Err(Error::InsufficientBalance)
+            }
+            self.apply_transfer(from, to, value)
+        }
+
+        /// Returns `Error::AccountFrozen` if `account` is frozen.
+        fn ensure_not_frozen(&self, account: &AccountId) -> Result<()> {
+            if self.frozen.contains_key(account) {
+                return Err(Error::AccountFrozen)
+            }
+            Ok(())
+        }
+
+        /// Returns `Error::TransferExceedsMax` if `value` exceeds the transfer limit.
+        fn ensure_within_max_transfer(&self, value: Balance) -> Result<()> {
+            match self.max_transfer {
+                Some(limit) if value > limit => Err(Error::TransferExceedsMax),
+                _ => Ok(()),
+            }
+        }
+
+        /// Mints tokens to every recipient of `mints`.
+        ///
+        /// Only the contract owner may call this. Either all mints are applied
+        /// or none: the sum of all amounts must neither overflow nor make the
+        /// total supply exceed the cap. The total supply is updated once and a
+        /// `Transfer` event is emitted per recipient.
+        #[ink(message)]
+        pub fn batch_mint(&mut self, mints: Vec<(AccountId, Balance)>) -> Result<()> {
+            if self.entered {
+                return Err(Error::ReentrantCall)
+            }
+            self.ensure_owner()?;
+            if self.mint_paused {
+                return Err(Error::Paused)
+            }
+            let total_supply = mints
+                .iter()
+                .try_fold(self.total_supply, |total, (_, value)| {
+                    total.checked_add(*value)
+                })
+                .ok_or(Error::CapExceeded)?;
+            if matches!(self.cap, Some(cap) if total_supply > cap) {
+                return Err(Error::CapExceeded)
+            }
+            // Accrue the rewards of all recipients before any balance is changed.
+            for (to, _) in &mints {
+                self.update_reward(to)?;
+            }
+            self.total_supply = total_supply;
+            for (to, value) in mints {
+                let balance = self.balance_of_or_zero(&to);
+                self.update_snapshot(&to);
+                self.balances.insert(to, balance + value);
+                self.env().emit_event(Transfer {
+                    from: self.transfer_party(None),
+                    to: Some(to),
+                    value,
+                });
+            }
+            Ok(())
+        }
+
+        /// Replaces all balances with `balances`, e.g. in order to migrate from a
+        /// prototype that stored them as a single vector.
+        ///
+        /// Only the contract owner may call this. The total supply is recomputed
+        /// from the migrated balances which must neither overflow nor exceed the
+        /// cap. Returns `Error::DuplicateAccount` if an account is listed more
+        /// than once. Either all balances are migrated or none, no events are
+        /// emitted.
+        ///
+        /// The reward pool keeps its balance so that accrued rewards can still be
+        /// claimed. It therefore counts as listed while it holds any tokens.
+        #[ink(message)]
+        pub fn migrate_from_vec(
+            &mut self,
+            balances: Vec<(AccountId, Balance)>,
+        ) -> Result<()> {
+            self.ensure_owner()?;
+            let mut accounts = balances
+                .iter()
+                .map(|(account, _)| *account)
+                .collect::<Vec<_>>();
+            accounts.sort_unstable();
+            if accounts.windows(2).any(|pair| pair[0] == pair[1]) {
+                return Err(Error::DuplicateAccount)
+            }
+            let pool = self.env().account_id();
+            let pooled = self.balance_of_or_zero(&pool);
+            if pooled != 0 && accounts.binary_search(&pool).is_ok() {
+                return Err(Error::DuplicateAccount)
+            }
+            let total_supply = balances
+                .iter()
+                .try_fold(pooled, |total: Balance, (_, value)| total.checked_add(*value))
+                .ok_or(Error::CapExceeded)?;
+            if matches!(self.cap, Some(cap) if total_supply > cap) {
+                return Err(Error::CapExceeded)
+            }
+            // Record the balances of the current and future holders for the
+            // current snapshot before they are replaced.
+            let holders = self.balances.keys().copied().collect::<Vec<_>>();
+            for account in holders.iter().chain(&accounts) {
+                self.update_reward(account)?;
+                self.update_snapshot(account);
+            }
+            self.balances.clear();
+            self.balances.extend(balances);
+            if pooled != 0 {
+                self.balances.insert(pool, pooled);
+            }
+            self.total_supply = total_supply;
+            Ok(())
+        }
+
+        /// Removes the transfer rate limit.
+        ///
+        /// Only the contract owner may call this.
+        #[ink(message)]
+        pub fn clear_rate_limit(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            self.rate_limit = None;
+            Ok(())
+        }
+
+        /// Accounts `value` to the transfers of `from` within the current window.
+        ///
+        /// The window of the token holder is charged, also if a spender moves the
+        /// tokens via `transfer_from`. Returns `Error::RateLimited` if this
+        /// exceeds the rate limit.
+        fn consume_rate_limit(&mut self, from: AccountId, value: Balance) -> Result<()> {
+            let (window, limit) = match self.rate_limit {
+                Some(rate_limit) => rate_limit,
+                None => return Ok(()),
+            };
+            let now = self.env().block_timestamp();
+            let (start, used) = match self.transfer_windows.get(&from) {
+                Some(&(start, used)) if now < start.saturating_add(window) => (start, used),
+                // The previous window has expired, start a new one.
+                _ => (now, 0),
+            };
+            let used = used
+                .checked_add(value)
+                .filter(|used| *used <= limit)
+                .ok_or(Error::RateLimited)?;
+            self.transfer_windows.insert(from, (start, used));
+            Ok(())
+        }
+
+        /// Returns `Error::NotOwner` if the caller is not the contract owner.
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            Ok(())
+        }
+
+        /// check the balance of the owner
+        #[ink(message)]
+        pub fn balance_of(&self, owner: AccountId) -> Balance {
+            // ACTION: Return the balance of `owner`
+            Self::balance_of_or_zero(&self, &owner)
+        }
+
+        fn balance_of_or_zero(&self, owner: &AccountId) -> Balance {
+            // ACTION: `get` the balance of `owner`, then `unwrap_or` fallback to 0
+            *self.balances.get(owner).unwrap_or(&0)
+        }
+
+        /// Approve the passed AccountId to spend the specified amount of tokens
+        /// on the behalf of the message's sender.
+        #[ink(message)]
+        pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
+            // ACTION: Get the `self.env().caller()` and store it as the `owner`
+            let owner = self.env().caller();
+
+            // Mitigate the approval race by forcing a reset to zero in between.
+            if self.strict_approve
+                && value != 0
+                && self.allowance_of_or_zero(&owner, &spender) != 0
+            {
+                return Err(Error::UnsafeApproval)
+            }
+
+            // ACTION: Insert the new allowance into the `allowances` HashMap
+            self.allowances.insert((owner, spender), value);
+            self.allowance_expiries.take(&(owner, spender));
+
+            // ACTION: `emit` the `Approval` event you created using these values
+            self.env()
+                .emit_event(
+                    Approval {
+                        owner: Some(owner),
+                        spender: Some(spender),
+                        value,
+                    }
+                );
+
+            Ok(())
+        }
+
+        /// Increases the allowance of `spender` by `delta` and emits an `Approval` with the
+        /// new total.
+        ///
+        /// A missing or expired allowance counts as zero, in which case the increased
+        /// allowance no longer expires. Unlike `approve` this is not subject to
+        /// strict approval mode since it does not suffer from the approval race.
+        #[ink(message)]
+        pub fn increase_allowance(&mut self, spender: AccountId, delta: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            let allowance = self.allowance_of_or_zero(&owner, &spender);
+            let value = allowance
+                .checked_add(delta)
+                .ok_or(Error::AllowanceOverflow)?;
+            if self.is_allowance_expired(&owner, &spender) {
+                self.allowance_expiries.take(&(owner, spender));
+            }
+            self.allowances.insert((owner, spender), value);
+            self.env().emit_event(Approval {
+                owner: Some(owner),
+                spender: Some(spender),
+                value,
+            });
+            Ok(())
+        }
+
+        /// Approves `spender` like `approve` but only until the `expires_at` timestamp.
+        ///
+        /// From `expires_at` on the allowance is treated as zero.
+        #[ink(message)]
+        pub fn approve_until(
+            &mut self,
+            spender: AccountId,
+            value: Balance,
+            expires_at: Timestamp,
+        ) -> Result<()> {
+            self.approve(spender, value)?;
+            let owner = self.env().caller();
+            self.allowance_expiries.insert((owner, spender), expires_at);
+            self.expiry_queue.push(QueuedExpiry {
+                expires_at,
+                owner,
+                spender,
+            });
+            Ok(())
+        }
+
+        /// Removes up to `max` expired allowances and returns how many were removed.
+        ///
+        /// An `AllowanceExpired` event is emitted for every removed allowance.
+        ///
+        /// # Note
+        ///
+        /// Expiries are visited in order of their point in time, so at most `max`
+        /// queued expiries are popped regardless of how many allowances exist.
+        /// Each pop costs `O(log n)` storage accesses for `n` queued expiries.
+        /// Stale expiries of allowances that have been changed since are dropped
+        /// without removing anything but still count against `max`.
+        #[ink(message)]
+        pub fn prune_expired_allowances(&mut self, max: u32) -> u32 {
+            let now = self.env().block_timestamp();
+            let mut removed = 0;
+            for _ in 0..max {
+                match self.expiry_queue.peek() {
+                    Some(queued) if now >= queued.expires_at => (),
+                    _ => break,
+                }
+                let QueuedExpiry {
+                    expires_at,
+                    owner,
+                    spender,
+                } = self.expiry_queue.pop().expect("an expiry has just been peeked");
+                let current = self.allowance_expiries.get_pair(&owner, &spender);
+                if current != Some(&expires_at) {
+                    continue
+                }
+                self.allowances.take_pair(&owner, &spender);
+                self.allowance_expiries.take_pair(&owner, &spender);
+                self.env().emit_event(AllowanceExpired { owner, spender });
+                removed += 1;
+            }
+            removed
+        }
+
+        #[ink(message)]
+        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            // ACTION: Create a getter for the `allowances` HashMap
+            Self::allowance_of_or_zero(self, &owner, &spender)
+        }
+
+        fn allowance_of_or_zero(&self, owner: &AccountId, spender: &AccountId) -> Balance {
+            // ACTION: `get` the `allowances` of `(owner, spender)` and `unwrap_or` return `0`.
+            if self.is_allowance_expired(owner, spender) {
+                return 0
+            }
+            *self.allowances.get_pair(owner, spender).unwrap_or(&0)
+        }
+
+        /// Returns `true` if the allowance of `spender` over the tokens of `owner` has expired.
+        fn is_allowance_expired(&self, owner: &AccountId, spender: &AccountId) -> bool {
+            // The timestamp is only queried for allowances that expire at all.
+            match self.allowance_expiries.get_pair(owner, spender) {
+                Some(expires_at) => self.env().block_timestamp() >= *expires_at,
+                None => false,
+            }
+        }
+
+        #[ink(message)]
+        pub fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            // Checked upfront so that the allowance is left untouched.
+            self.ensure_transfers_not_paused()?;
+            self.ensure_not_frozen(&from)?;
+            // Spending one's own tokens does not require an allowance.
+            if from == self.env().caller() {
+                return Self::transfer_from_to(self, from, to, value)
+            }
+
+            // ACTION: Get the allowance for `(from, self.env().caller())` using `allowance_of_or_zero`
+            let allowance = Self::allowance_of_or_zero(self, &from, &self.env().caller());
+
+            // ACTION: `if` the `allowance` is less than the `value`, exit early and return `false`
+            if allowance < value {
+                return Err(Error::InsufficientAllowance);
+            }
+
+            // ACTION: Finally, call the `transfer_from_to` for `from` and `to`\
+            // ACTION: Return true if everything was successful
+            Self::transfer_from_to(self, from, to, value)?;
+
+            // ACTION: `insert` the new allowance into the map for `(from, self.env().caller())`
+            // Only spent once the transfer has passed all of its checks.
+            self.allowances.insert((from, self.env().caller()), allowance - value);
+            Ok(())
+        }
+
+        /// Burns `value` tokens of `from` on behalf of the caller.
+        ///
+        /// The caller needs an allowance of at least `value` over the tokens of `from`.
+        /// An allowance of `Balance::MAX` is treated as infinite and not decremented.
+        /// Like transfers, burns from a frozen account are rejected.
+        #[ink(message)]
+        pub fn burn_from(&mut self, from: AccountId, value: Balance) -> Result<()> {
+            if self.entered {
+                return Err(Error::ReentrantCall)
+            }
+            if self.burn_paused {
+                return Err(Error::Paused)
+            }
+            self.ensure_not_frozen(&from)?;
+            let caller = self.env().caller();
+            let allowance = self.allowance_of_or_zero(&from, &caller);
+            if allowance < value {
+                return Err(Error::InsufficientAllowance)
+            }
+            let balance = self.balance_of_or_zero(&from);
+            let new_balance = balance
+                .checked_sub(value)
+                .ok_or(Error::InsufficientBalance)?;
+            let total_supply = self
+                .total_supply
+                .checked_sub(value)
+                .expect("the total supply cannot be less than a balance");
+
+            self.update_reward(&from)?;
+            if allowance != Balance::MAX {
+                self.allowances.insert((from, caller), allowance - value);
+            }
+            self.update_snapshot(&from);
+            self.balances.insert(from, new_balance);
+            self.total_supply = total_supply;
+
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: self.transfer_party(None),
+                value,
+            });
+            Ok(())
+        }
+
+        /// transfer the balance from sender to receiver
+        #[ink(message)]
+        pub fn transfer(&mut self, to: AccountId, value: Balance) -> Result<()> {
+            // ACTION: Call the `transfer_from_to` with `from` as `self.env().caller()`
+            Self::transfer_from_to(self,self.env().caller(), to, value)
+        }
+
+        /// Transfers like `transfer` and returns the caller's balance afterwards.
+        #[ink(message)]
+        pub fn transfer_returning_balance(
+            &mut self,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<Balance> {
+            let caller = self.env().caller();
+            self.transfer_from_to(caller, to, value)?;
+            Ok(self.balance_of_or_zero(&caller))
+        }
+
+        /// Transfers to `to` and then invokes its `on_token_received` message.
+        ///
+        /// The balances are updated and the `Transfer` event is emitted before
+        /// the recipient is invoked. Any tokens moved while the recipient is
+        /// invoked fail with `Error::ReentrantCall`. If the recipient fails
+        /// `Error::TransferRejected` is returned.
+        ///
+        /// # Note
+        ///
+        /// The transfer is rolled back only because returning an error reverts
+        /// all state changes of the message on-chain, including the event and
+        /// the consumed rate limit. The instance itself is not restored.
+        #[ink(message)]
+        pub fn transfer_and_call(
+            &mut self,
+            to: AccountId,
+            value: Balance,
+            data: Vec<u8>,
+        ) -> Result<()> {
+            let from = self.env().caller();
+            self.transfer_from_to(from, to, value)?;
+
+            let accepted = Entered::new(self)
+                .contract
+                .call_token_received(from, to, value, data);
+            if !accepted {
+                return Err(Error::TransferRejected)
+            }
+            Ok(())
+        }
+
+        /// Invokes `on_token_received` of `to` and returns whether it succeeded.
+        ///
+        /// Reentrant calls into this contract are rejected by the runtime since
+        /// the call flags do not allow reentry.
+        #[cfg(not(test))]
+        fn call_token_received(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            data: Vec<u8>,
+        ) -> bool {
+            use ink_env::call::{
+                build_call,
+                ExecutionInput,
+                Selector,
+            };
+            build_call::<ink_env::DefaultEnvironment>()
+                .callee(to)
+                .gas_limit(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ON_TOKEN_RECEIVED_SELECTOR))
+                        .push_arg(from)
+                        .push_arg(value)
+                        .push_arg(data),
+                )
+                .returns::<()>()
+                .fire()
+                .is_ok()
+        }
+
+        /// Invokes the `TOKEN_RECEIVED_HOOK` if set, accepting otherwise.
+        #[cfg(test)]
+        fn call_token_received(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            _data: Vec<u8>,
+        ) -> bool {
+            match TOKEN_RECEIVED_HOOK.with(|hook| hook.get()) {
+                Some(hook) => hook(self, from, to, value),
+                None => true,
+            }
+        }
+
+        /// Transfers from the caller to every recipient of `transfers`.
+        ///
+        /// Either all transfers are applied or none: the caller's balance must
+        /// cover the sum of all values and at most `MAX_BATCH_TRANSFERS` transfers
+        /// may be batched. A `Transfer` event is emitted per recipient.
+        #[ink(message)]
+        pub fn batch_transfer(&mut self, transfers: Vec<(AccountId, Balance)>) -> Result<()> {
+            if transfers.len() > MAX_BATCH_TRANSFERS {
+                return Err(Error::BatchTooLarge)
+            }
+            if self.entered {
+                return Err(Error::ReentrantCall)
+            }
+            self.ensure_transfers_not_paused()?;
+            let from = self.env().caller();
+            self.ensure_not_frozen(&from)?;
+            // A total that overflows can never be covered by any balance.
+            let total = transfers
+                .iter()
+                .try_fold(0 as Balance, |total, (_, value)| total.checked_add(*value))
+                .ok_or(Error::InsufficientBalance)?;
+            if self.balance_of_or_zero(&from) < total {
+                return Err(Error::InsufficientBalance)
+            }
+            for (_, value) in &transfers {
+                self.ensure_within_max_transfer(*value)?;
+            }
+            // Accrue the rewards of all parties before any state is changed.
+            self.update_reward(&from)?;
+            for (to, _) in &transfers {
+                self.update_reward(to)?;
+            }
+            self.consume_rate_limit(from, total)?;
+            for (to, value) in transfers {
+                self.apply_transfer(from, to, value)?;
+            }
+            Ok(())
+        }
+
+        fn transfer_from_to(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            if self.entered {
+                return Err(Error::ReentrantCall)
+            }
+            self.ensure_transfers_not_paused()?;
+            self.ensure_not_frozen(&from)?;
+            // ACTION: If `from_balance` is less than `value`, return `false`
+            if Self::balance_of_or_zero(self, &from) < value {
+                return Err(Error::InsufficientBalance);
+            }
+
+            self.ensure_within_max_transfer(value)?;
+            // Accrue the rewards of both parties before any state is changed.
+            self.update_reward(&from)?;
+            self.update_reward(&to)?;
+            self.consume_rate_limit(from, value)?;
+            self.apply_transfer(from, to, value)
+        }
+
+        /// Returns the `Transfer` party to emit for `account`.
+        ///
+        /// An absent party is emitted as the zero `AccountId` if the contract
+        /// has been configured with `zero_address_topics`.
+        fn transfer_party(&self, account: Option<AccountId>) -> Option<AccountId> {
+            match account {
+                None if self.zero_address_topics => Some(AccountId::from([0x0; 32])),
+                account => account,
+            }
+        }
+
+        /// Moves `value` from `from` to `to` and emits a `Transfer` event.
+        ///
+        /// The caller must ensure that `from` holds at least `value`. Returns
+        /// `Error::RewardOverflow` before changing any balance if the rewards of
+        /// either party overflow.
+        fn apply_transfer(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            self.update_reward(&from)?;
+            self.update_reward(&to)?;
+            self.update_snapshot(&from);
+            self.update_snapshot(&to);
+
+            // ACTION: Insert new values for `from` and `to`
+            //         * from_balance - value
+            let balance_from = Self::balance_of_or_zero(self, &from);
+            self.balances.insert(from, balance_from - value);
+            // Read after `from` has been debited so that a transfer to oneself
+            // leaves the balance unchanged instead of minting `value`.
+            let balance_to = Self::balance_of_or_zero(self, &to);
+            self.balances.insert(to, balance_to + value);
+
+            self.env()
+                .emit_event(
+                    Transfer {
+                        from: Some(from),
+                        to: Some(to),
+                        value,
+                    }
+                );
+            Ok(())
+        }
+    }
+
+    /// Renders an amount of base units as a decimal string with `decimals` fractional digits.
+    ///
+    /// E.g. a `value` of `10000` with `2` decimals is rendered as `100.00`.
+    /// The fractional digits are always padded to `decimals` digits so that
+    /// amounts line up; with `0` decimals no decimal point is rendered.
+    #[cfg(feature = "std")]
+    pub fn format_amount(value: Balance, decimals: u8) -> String {
+        let digits = value.to_string();
+        let decimals = decimals as usize;
+        if decimals == 0 {
+            return digits
+        }
+        // Pad with leading zeros so that there is at least one integer digit.
+        let padded = format!("{:0>width$}", digits, width = decimals + 1);
+        let (integer, fraction) = padded.split_at(padded.len() - decimals);
+        format!("{}.{}", integer, fraction)
+    }
+
+    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
+    /// module and test functions are marked with a `#[test]` attribute.
+    /// The below code is technically just normal Rust code.
+    #[cfg(feature = "ink-experimental-engine")]
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ink_lang as ink;
+
+        type Event = <Erc20 as ::ink_lang::reflect::ContractEventBase>::Type;
+
+        #[ink::test]
+        fn new_works() {
+            let contract = Erc20::new(777);
+            assert_eq!(contract.total_supply(), 777);
+        }
+
+        // the default address is AccountId::from([0x1; 32])
+        #[ink::test]
+        fn balance_works() {
+            let contract = Erc20::new(100);
+            assert_eq!(contract.total_supply(), 100);
             assert_eq!(contract.balance_of(AccountId::from([0x1; 32])), 100);
+            assert_eq!(contract.balance_of(AccountId::from([0x0; 32])), 0);
+        }
+
+        #[ink::test]
+        fn transfer_works() {
+            let mut contract = Erc20::new(100);
+            assert_eq!(contract.total_supply(), 100);
+            contract.transfer(AccountId::from([0x2; 32]), 20);
+
+            assert_eq!(contract.balance_of(AccountId::from([0x1; 32])), 80);
+            assert_eq!(contract.balance_of(AccountId::from([0x2; 32])), 20);
+        }
+
+        #[ink::test]
+        fn transfer_event_topics_work() {
+            let mut contract = Erc20::new(100);
+            let from = AccountId::from([0x1; 32]);
+            let to = AccountId::from([0x2; 32]);
+            assert_eq!(contract.transfer(to, 20), Ok(()));
+
+            let index = ink_env::test::emitted_event_count() - 1;
+            let topics = ink_env::test::event_topics::<Environment>(index);
+            // The first topic is the event signature, followed by the indexed fields.
+            assert_eq!(topics.len(), 3);
+            assert_eq!(
+                topics[1],
+                ink_env::test::event_topic::<Environment, _>(
+                    b"Erc20::Transfer::from",
+                    &Some(from)
+                )
+            );
+            assert_eq!(
+                topics[2],
+                ink_env::test::event_topic::<Environment, _>(
+                    b"Erc20::Transfer::to",
+                    &Some(to)
+                )
+            );
+            assert_ne!(topics[1], topics[2]);
+        }
+
+        #[ink::test]
+        fn transfer_insufficient() {
+            let mut contract = Erc20::new(100);
+
+            assert_eq!(contract.total_supply(), 100);
+            assert_eq!(contract.balance_of(AccountId::from([0x1; 32])), 100);
+
+            assert_eq!(
+                contract.transfer(AccountId::from([0x2; 32]), 120),
+                Err(Error::InsufficientBalance)
+            );
+        }
+
+        #[ink::test]
+        fn allowance_works() {
+            let mut contract = Erc20::new(100);
+            assert_eq!(contract.total_supply(), 100);
+
+            contract.approve(AccountId::from([0x2; 32]), 20);
+
+            assert_eq!(contract.allowance(
+                AccountId::from([0x1; 32]),
+                AccountId::from([0x2; 32])
+            ), 20);
+        }
+
+        #[ink::test]
+        fn transfer_from_works() {
+            let mut contract = Erc20::new(100);
+            assert_eq!(contract.balance_of(AccountId::from([0x1; 32])), 100);
+
+            contract.approve(AccountId::from([0x2; 32]), 20);
+            let contract_addr = ink_env::account_id::<ink_env::DefaultEnvironment>();
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract_addr);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x2; 32]));
+
+            contract.transfer_from(AccountId::from([0x1; 32]), AccountId::from([0x3; 32]), 10);
+            assert_eq!(contract.balance_of(AccountId::from([0x3; 32])), 10);
+        }
+
+        #[ink::test]
+        fn transfer_from_insufficient() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+
+            // Check the balance of alice (default caller) and set 20 allowance to bob
+            assert_eq!(contract.balance_of(accounts.alice), 100);
+            contract.approve(accounts.bob, 20);
+
+            // Set the contract as callee and Bob as caller.
+            let contract_addr = ink_env::account_id::<ink_env::DefaultEnvironment>();
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract_addr);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+
+            assert_eq!(
+                contract.transfer_from(accounts.alice, accounts.charlie, 30),
+                Err(Error::InsufficientAllowance)
+            );
+        }
+
+        #[ink::test]
+        fn transfer_and_approve_emit_one_event() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            // The constructor emits the initial `Transfer`.
+            ink_env::test::assert_event_count::<ink_env::DefaultEnvironment>(1);
+
+            assert_eq!(contract.transfer(accounts.bob, 10), Ok(()));
+            ink_env::test::assert_event_count::<ink_env::DefaultEnvironment>(2);
+
+            assert_eq!(contract.approve(accounts.bob, 10), Ok(()));
+            ink_env::test::assert_event_count::<ink_env::DefaultEnvironment>(3);
+
+            // Failing transfers do not emit events.
+            assert_eq!(
+                contract.transfer(accounts.bob, 1000),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(ink_env::test::emitted_event_count(), 3);
+        }
+
+        #[ink::test]
+        fn batch_transfer_works() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(
+                contract.batch_transfer(vec![(accounts.bob, 10), (accounts.charlie, 20)]),
+                Ok(())
+            );
+            assert_eq!(contract.balance_of(accounts.alice), 70);
+            assert_eq!(contract.balance_of(accounts.bob), 10);
+            assert_eq!(contract.balance_of(accounts.charlie), 20);
+            // One `Transfer` per recipient next to the one of the constructor.
+            ink_env::test::assert_event_count::<ink_env::DefaultEnvironment>(3);
+        }
+
+        #[ink::test]
+        fn self_transfers_leave_balance_and_supply_unchanged() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.transfer(accounts.alice, 40), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 100);
+            assert_eq!(
+                contract.batch_transfer(vec![(accounts.alice, 3); MAX_BATCH_TRANSFERS]),
+                Ok(())
+            );
+            assert_eq!(contract.balance_of(accounts.alice), 100);
+            assert_eq!(contract.total_supply(), 100);
+            // The `Transfer` events are emitted nevertheless.
+            ink_env::test::assert_event_count::<ink_env::DefaultEnvironment>(
+                2 + MAX_BATCH_TRANSFERS,
+            );
+        }
+
+        #[ink::test]
+        fn batch_transfer_insufficient_total_fails() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            // Every single transfer is covered but their sum is not.
+            assert_eq!(
+                contract.batch_transfer(vec![(accounts.bob, 60), (accounts.charlie, 60)]),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(
+                contract.batch_transfer(vec![(accounts.bob, Balance::MAX), (accounts.charlie, 1)]),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(contract.balance_of(accounts.alice), 100);
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+            assert_eq!(contract.balance_of(accounts.charlie), 0);
+            ink_env::test::assert_event_count::<ink_env::DefaultEnvironment>(1);
+        }
+
+        #[ink::test]
+        fn batch_transfer_length_is_bounded() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(
+                contract.batch_transfer(vec![(accounts.bob, 1); MAX_BATCH_TRANSFERS + 1]),
+                Err(Error::BatchTooLarge)
+            );
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+            assert_eq!(
+                contract.batch_transfer(vec![(accounts.bob, 1); MAX_BATCH_TRANSFERS]),
+                Ok(())
+            );
+            assert_eq!(contract.balance_of(accounts.bob), MAX_BATCH_TRANSFERS as Balance);
+        }
+
+        #[ink::test]
+        fn increase_allowance_creates_absent_allowance() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.increase_allowance(accounts.bob, 10), Ok(()));
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 10);
+            ink_env::test::assert_event_count::<ink_env::DefaultEnvironment>(2);
+        }
+
+        #[ink::test]
+        fn increase_allowance_adds_to_existing_allowance() {
+            let mut contract = Erc20::new_with_strict_approve(100, true);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, 10), Ok(()));
+            // Not subject to strict approval mode.
+            assert_eq!(contract.increase_allowance(accounts.bob, 5), Ok(()));
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 15);
+
+            // The `Approval` event carries the new total.
+            let approval = ink_env::test::recorded_events()
+                .last()
+                .expect("an `Approval` event must have been emitted");
+            let decoded = <Event as scale::Decode>::decode(&mut &approval.data[..])
+                .expect("encountered invalid contract event data buffer");
+            match decoded {
+                Event::Approval(Approval { owner, spender, value }) => {
+                    assert_eq!(owner, Some(accounts.alice));
+                    assert_eq!(spender, Some(accounts.bob));
+                    assert_eq!(value, 15);
+                }
+                _ => panic!("expected an `Approval` event"),
+            }
+        }
+
+        #[ink::test]
+        fn increase_allowance_rejects_overflow() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, Balance::MAX - 1), Ok(()));
+            assert_eq!(contract.increase_allowance(accounts.bob, 1), Ok(()));
+            assert_eq!(
+                contract.increase_allowance(accounts.bob, 1),
+                Err(Error::AllowanceOverflow)
+            );
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), Balance::MAX);
+            ink_env::test::assert_event_count::<ink_env::DefaultEnvironment>(3);
+        }
+
+        #[ink::test]
+        fn burn_from_works() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, 30), Ok(()));
+            assert_eq!(contract.approve(accounts.charlie, Balance::MAX), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.burn_from(accounts.alice, 20), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 80);
+            assert_eq!(contract.total_supply(), 80);
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 10);
+
+            // Infinite allowances are not decremented.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.burn_from(accounts.alice, 50), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 30);
+            assert_eq!(contract.total_supply(), 30);
+            assert_eq!(
+                contract.allowance(accounts.alice, accounts.charlie),
+                Balance::MAX
+            );
+
+            let burned = ink_env::test::recorded_events()
+                .last()
+                .expect("a `Transfer` event must have been emitted");
+            let decoded = <Event as scale::Decode>::decode(&mut &burned.data[..])
+                .expect("encountered invalid contract event data buffer");
+            match decoded {
+                Event::Transfer(Transfer { from, to, value }) => {
+                    assert_eq!(from, Some(accounts.alice));
+                    assert_eq!(to, None);
+                    assert_eq!(value, 50);
+                }
+                _ => panic!("expected a `Transfer` event"),
+            }
+        }
+
+        #[ink::test]
+        fn burn_from_rejects_frozen_accounts() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, 30), Ok(()));
+            assert_eq!(contract.freeze(accounts.alice), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.burn_from(accounts.alice, 10),
+                Err(Error::AccountFrozen)
+            );
+            assert_eq!(contract.balance_of(accounts.alice), 100);
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 30);
+            assert_eq!(contract.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn burn_from_fails_for_insufficient_allowance_or_balance() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(contract.approve(accounts.charlie, 500), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.burn_from(accounts.alice, 11),
+                Err(Error::InsufficientAllowance)
+            );
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                contract.burn_from(accounts.alice, 101),
+                Err(Error::InsufficientBalance)
+            );
+            // Failed burns leave everything untouched.
+            assert_eq!(contract.balance_of(accounts.alice), 100);
+            assert_eq!(contract.total_supply(), 100);
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 10);
+            assert_eq!(contract.allowance(accounts.alice, accounts.charlie), 500);
+        }
+
+        #[ink::test]
+        fn strict_approve_requires_reset_to_zero() {
+            let mut contract = Erc20::new_with_strict_approve(100, true);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(
+                contract.approve(accounts.bob, 20),
+                Err(Error::UnsafeApproval)
+            );
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 10);
+
+            // Changing the allowance is possible via zero.
+            assert_eq!(contract.approve(accounts.bob, 0), Ok(()));
+            assert_eq!(contract.approve(accounts.bob, 20), Ok(()));
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 20);
+
+            // Allowances of other spenders are independent.
+            assert_eq!(contract.approve(accounts.charlie, 5), Ok(()));
+        }
+
+        #[ink::test]
+        fn permissive_approve_is_unchanged() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(contract.approve(accounts.bob, 20), Ok(()));
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 20);
+        }
+
+        #[test]
+        fn format_amount_works() {
+            assert_eq!(format_amount(10000, 2), "100.00");
+            assert_eq!(format_amount(12345, 2), "123.45");
+            assert_eq!(format_amount(12345, 0), "12345");
+            assert_eq!(format_amount(12345, 5), "0.12345");
+            assert_eq!(format_amount(5, 3), "0.005");
+            assert_eq!(format_amount(0, 0), "0");
+            assert_eq!(format_amount(0, 2), "0.00");
+            assert_eq!(
+                format_amount(Balance::MAX, 18),
+                "340282366920938463463.374607431768211455"
+            );
+            assert_eq!(format_amount(1, 40), format!("0.{:0>40}", 1));
+        }
+
+        #[ink::test]
+        fn transfer_from_self_works_without_allowance() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.allowance(accounts.alice, accounts.alice), 0);
+
+            assert_eq!(
+                contract.transfer_from(accounts.alice, accounts.bob, 30),
+                Ok(())
+            );
+            assert_eq!(contract.balance_of(accounts.alice), 70);
+            assert_eq!(contract.balance_of(accounts.bob), 30);
+            assert_eq!(contract.allowance(accounts.alice, accounts.alice), 0);
+
+            // Third-party spends still require an allowance.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.transfer_from(accounts.alice, accounts.charlie, 10),
+                Err(Error::InsufficientAllowance)
+            );
+        }
+
+        #[ink::test]
+        fn circulating_supply_excludes_non_circulating() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let treasury = accounts.django;
+            assert_eq!(contract.circulating_supply(), 100);
+
+            // An account without balance is treated as holding zero.
+            assert_eq!(contract.add_non_circulating(treasury), Ok(()));
+            assert_eq!(contract.circulating_supply(), 100);
+
+            // Funding the treasury removes its balance from circulation.
+            assert_eq!(contract.transfer(treasury, 30), Ok(()));
+            assert_eq!(contract.total_supply(), 100);
+            assert_eq!(contract.circulating_supply(), 70);
+
+            // Adding the same account twice must not count it twice.
+            assert_eq!(
+                contract.add_non_circulating(treasury),
+                Err(Error::AlreadyNonCirculating)
+            );
+            assert_eq!(contract.circulating_supply(), 70);
+
+            assert_eq!(contract.remove_non_circulating(treasury), Ok(()));
+            assert_eq!(contract.circulating_supply(), 100);
+            assert_eq!(
+                contract.remove_non_circulating(treasury),
+                Err(Error::NotNonCirculating)
+            );
+        }
+
+        #[ink::test]
+        fn non_circulating_is_owner_managed() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.add_non_circulating(accounts.django),
+                Err(Error::NotOwner)
+            );
+            assert_eq!(contract.circulating_supply(), 100);
+        }
+
+        #[ink::test]
+        fn balance_of_at_works() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(
+                contract.balance_of_at(accounts.alice, 1),
+                Err(Error::InvalidSnapshot)
+            );
+
+            assert_eq!(contract.snapshot(), Ok(1));
+            assert_eq!(contract.transfer(accounts.bob, 30), Ok(()));
+
+            // The snapshot keeps the pre-transfer balances.
+            assert_eq!(contract.balance_of_at(accounts.alice, 1), Ok(100));
+            assert_eq!(contract.balance_of_at(accounts.bob, 1), Ok(0));
+            assert_eq!(contract.balance_of(accounts.alice), 70);
+            assert_eq!(contract.balance_of(accounts.bob), 30);
+
+            // Only the first change after a snapshot is recorded.
+            assert_eq!(contract.transfer(accounts.bob, 20), Ok(()));
+            assert_eq!(contract.balance_of_at(accounts.alice, 1), Ok(100));
+
+            // Snapshots without subsequent changes report the current balance.
+            assert_eq!(contract.snapshot(), Ok(2));
+            assert_eq!(contract.snapshot(), Ok(3));
+            assert_eq!(contract.balance_of_at(accounts.alice, 2), Ok(50));
+            assert_eq!(contract.transfer(accounts.bob, 10), Ok(()));
+            assert_eq!(contract.balance_of_at(accounts.alice, 1), Ok(100));
+            assert_eq!(contract.balance_of_at(accounts.alice, 2), Ok(50));
+            assert_eq!(contract.balance_of_at(accounts.alice, 3), Ok(50));
+            assert_eq!(contract.balance_of(accounts.alice), 40);
+            assert_eq!(
+                contract.balance_of_at(accounts.alice, 4),
+                Err(Error::InvalidSnapshot)
+            );
+        }
+
+        #[ink::test]
+        fn transfer_returning_balance_works() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.transfer_returning_balance(accounts.bob, 30), Ok(70));
+            assert_eq!(contract.balance_of(accounts.alice), 70);
+            // Transferring everything that is left returns a zero balance.
+            assert_eq!(contract.transfer_returning_balance(accounts.bob, 70), Ok(0));
+            assert_eq!(contract.balance_of(accounts.alice), 0);
+            assert_eq!(contract.balance_of(accounts.bob), 100);
+            assert_eq!(
+                contract.transfer_returning_balance(accounts.bob, 1),
+                Err(Error::InsufficientBalance)
+            );
+        }
+
+        #[ink::test]
+        fn transfer_and_call_rejects_reentrant_transfers() {
+            // The recipient tries to spend the received tokens twice.
+            TOKEN_RECEIVED_HOOK.with(|hook| {
+                hook.set(Some(|contract, from, to, value| {
+                    let accounts =
+                        ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+                    // The balances have already been updated.
+                    assert_eq!(contract.balance_of(from), 100 - value);
+                    assert_eq!(contract.balance_of(to), value);
+                    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(to);
+                    assert_eq!(
+                        contract.transfer(accounts.charlie, value),
+                        Err(Error::ReentrantCall)
+                    );
+                    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(from);
+                    true
+                }))
+            });
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.transfer_and_call(accounts.bob, 30, Vec::new()), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 70);
+            assert_eq!(contract.balance_of(accounts.bob), 30);
+            assert_eq!(contract.balance_of(accounts.charlie), 0);
+            // Transfers are possible again once the recipient returned.
+            assert_eq!(contract.transfer(accounts.charlie, 10), Ok(()));
+            TOKEN_RECEIVED_HOOK.with(|hook| hook.set(None));
+        }
+
+        #[ink::test]
+        fn on_token_received_selector_is_default_selector() {
+            use ink_env::hash::{
+                Blake2x256,
+                HashOutput,
+            };
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink_env::hash_bytes::<Blake2x256>(b"on_token_received", &mut output);
+            assert_eq!(ON_TOKEN_RECEIVED_SELECTOR, output[0..4]);
+        }
+
+        #[ink::test]
+        fn transfer_and_call_fails_if_rejected() {
+            TOKEN_RECEIVED_HOOK.with(|hook| hook.set(Some(|_, _, _, _| false)));
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(
+                contract.transfer_and_call(accounts.bob, 30, Vec::new()),
+                Err(Error::TransferRejected)
+            );
+            // The error reverts the transfer on-chain, the instance is not restored.
+            assert_eq!(contract.balance_of(accounts.alice), 70);
+            assert_eq!(contract.balance_of(accounts.bob), 30);
+            assert_eq!(contract.total_supply(), 100);
+            assert!(!contract.entered);
+            TOKEN_RECEIVED_HOOK.with(|hook| hook.set(None));
+        }
+
+        #[ink::test]
+        fn transfer_and_call_resets_entered_if_recipient_panics() {
+            TOKEN_RECEIVED_HOOK
+                .with(|hook| hook.set(Some(|_, _, _, _| panic!("recipient trapped"))));
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                contract.transfer_and_call(accounts.bob, 30, Vec::new())
+            }));
+            assert!(result.is_err());
+            assert!(!contract.entered);
+            TOKEN_RECEIVED_HOOK.with(|hook| hook.set(None));
+            // Transfers are not rejected as reentrant after the panic.
+            assert_eq!(contract.transfer(accounts.charlie, 10), Ok(()));
+        }
+
+        #[ink::test]
+        fn transfer_and_call_rejects_reentrant_burn_from() {
+            // The recipient tries to burn the tokens it has been approved for.
+            TOKEN_RECEIVED_HOOK.with(|hook| {
+                hook.set(Some(|contract, from, to, value| {
+                    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(to);
+                    assert_eq!(
+                        contract.burn_from(from, value),
+                        Err(Error::ReentrantCall)
+                    );
+                    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(from);
+                    true
+                }))
+            });
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, 30), Ok(()));
+            assert_eq!(contract.transfer_and_call(accounts.bob, 30, Vec::new()), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 70);
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 30);
+            assert_eq!(contract.total_supply(), 100);
+            TOKEN_RECEIVED_HOOK.with(|hook| hook.set(None));
+        }
+
+        #[ink::test]
+        fn transfer_and_call_rejects_reentrant_batch_mint() {
+            // The owner's recipient tries to mint itself new tokens.
+            TOKEN_RECEIVED_HOOK.with(|hook| {
+                hook.set(Some(|contract, _, to, value| {
+                    assert_eq!(
+                        contract.batch_mint(vec![(to, value)]),
+                        Err(Error::ReentrantCall)
+                    );
+                    true
+                }))
+            });
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.transfer_and_call(accounts.bob, 30, Vec::new()), Ok(()));
+            assert_eq!(contract.balance_of(accounts.bob), 30);
+            assert_eq!(contract.total_supply(), 100);
+            TOKEN_RECEIVED_HOOK.with(|hook| hook.set(None));
+        }
+
+        #[ink::test]
+        fn transfer_and_call_rejects_reentrant_clawback() {
+            // The owner's recipient tries to claw back the tokens of a frozen account.
+            TOKEN_RECEIVED_HOOK.with(|hook| {
+                hook.set(Some(|contract, _, to, value| {
+                    let accounts =
+                        ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+                    assert_eq!(
+                        contract.clawback(accounts.charlie, to, value),
+                        Err(Error::ReentrantCall)
+                    );
+                    true
+                }))
+            });
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.transfer(accounts.charlie, 50), Ok(()));
+            assert_eq!(contract.freeze(accounts.charlie), Ok(()));
+            assert_eq!(contract.transfer_and_call(accounts.bob, 30, Vec::new()), Ok(()));
+            assert_eq!(contract.balance_of(accounts.bob), 30);
+            assert_eq!(contract.balance_of(accounts.charlie), 50);
+            TOKEN_RECEIVED_HOOK.with(|hook| hook.set(None));
+        }
+
+        #[ink::test]
+        fn batch_mint_works() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.set_cap(Some(200)), Ok(()));
+            assert_eq!(
+                contract.batch_mint(vec![(accounts.bob, 60), (accounts.charlie, 40)]),
+                Ok(())
+            );
+            assert_eq!(contract.total_supply(), 200);
+            assert_eq!(contract.balance_of(accounts.bob), 60);
+            assert_eq!(contract.balance_of(accounts.charlie), 40);
+            // One `Transfer` per recipient next to the one of the constructor.
+            ink_env::test::assert_event_count::<ink_env::DefaultEnvironment>(3);
+            let minted = ink_env::test::recorded_events()
+                .last()
+                .expect("a `Transfer` event must have been emitted");
+            let decoded = <Event as scale::Decode>::decode(&mut &minted.data[..])
+                .expect("encountered invalid contract event data buffer");
+            match decoded {
+                Event::Transfer(Transfer { from, to, value }) => {
+                    assert_eq!(from, None);
+                    assert_eq!(to, Some(accounts.charlie));
+                    assert_eq!(value, 40);
+                }
+                _ => panic!("expected a `Transfer` event"),
+            }
+        }
+
+        #[ink::test]
+        fn batch_mint_exceeding_cap_changes_nothing() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.set_cap(Some(150)), Ok(()));
+            assert_eq!(
+                contract.batch_mint(vec![(accounts.bob, 30), (accounts.charlie, 30)]),
+                Err(Error::CapExceeded)
+            );
+            assert_eq!(
+                contract.batch_mint(vec![
+                    (accounts.bob, 1),
+                    (accounts.charlie, Balance::MAX)
+                ]),
+                Err(Error::CapExceeded)
+            );
+            assert_eq!(contract.total_supply(), 100);
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+            assert_eq!(contract.balance_of(accounts.charlie), 0);
+            ink_env::test::assert_event_count::<ink_env::DefaultEnvironment>(1);
+
+            // Only the owner may mint.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.batch_mint(vec![(accounts.bob, 1)]),
+                Err(Error::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn migrate_from_vec_works() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            // The contract account holding the reward pool defaults to Alice,
+            // whose balance would otherwise be kept.
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(accounts.frank);
+            assert_eq!(
+                contract.migrate_from_vec(vec![
+                    (accounts.bob, 30),
+                    (accounts.charlie, 20),
+                    (accounts.django, 0),
+                ]),
+                Ok(())
+            );
+            assert_eq!(contract.total_supply(), 50);
+            // Balances that are not part of the migration are removed.
+            assert_eq!(contract.balance_of(accounts.alice), 0);
+            assert_eq!(contract.balance_of(accounts.bob), 30);
+            assert_eq!(contract.balance_of(accounts.charlie), 20);
+            assert_eq!(contract.balance_of(accounts.django), 0);
+            // The migrated balances can be transferred as usual.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.transfer(accounts.alice, 10), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 10);
+
+            // Only the owner may migrate.
+            assert_eq!(
+                contract.migrate_from_vec(vec![(accounts.bob, 1)]),
+                Err(Error::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn migrate_from_vec_rejects_duplicate_accounts() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(
+                contract.migrate_from_vec(vec![
+                    (accounts.bob, 30),
+                    (accounts.charlie, 20),
+                    (accounts.bob, 10),
+                ]),
+                Err(Error::DuplicateAccount)
+            );
+            assert_eq!(
+                contract.migrate_from_vec(vec![
+                    (accounts.bob, 1),
+                    (accounts.charlie, Balance::MAX)
+                ]),
+                Err(Error::CapExceeded)
+            );
+            // Nothing has been changed.
+            assert_eq!(contract.total_supply(), 100);
+            assert_eq!(contract.balance_of(accounts.alice), 100);
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+            assert_eq!(contract.balance_of(accounts.charlie), 0);
+        }
 
-            contract.approve(AccountId::from([0x2; 32]), 20);
-            let contract_addr = ink_env::account_id::<ink_env::DefaultEnvironment>();
-            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract_addr);
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x2; 32]));
+        #[ink::test]
+        fn max_transfer_works() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            // Unlimited by default.
+            assert_eq!(contract.max_transfer(), None);
+            assert_eq!(contract.transfer(accounts.bob, 30), Ok(()));
 
-            contract.transfer_from(AccountId::from([0x1; 32]), AccountId::from([0x3; 32]), 10);
-            assert_eq!(contract.balance_of(AccountId::from([0x3; 32])), 10);
+            assert_eq!(contract.set_max_transfer(Some(10)), Ok(()));
+            assert_eq!(contract.max_transfer(), Some(10));
+            // Transfers up to the limit succeed.
+            assert_eq!(contract.transfer(accounts.bob, 10), Ok(()));
+            assert_eq!(contract.transfer(accounts.bob, 5), Ok(()));
+            assert_eq!(contract.balance_of(accounts.bob), 45);
+            // Transfers over the limit are rejected, also within batches.
+            assert_eq!(
+                contract.transfer(accounts.bob, 11),
+                Err(Error::TransferExceedsMax)
+            );
+            assert_eq!(
+                contract.batch_transfer(vec![(accounts.bob, 5), (accounts.charlie, 11)]),
+                Err(Error::TransferExceedsMax)
+            );
+            assert_eq!(contract.approve(accounts.bob, 50), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.transfer_from(accounts.alice, accounts.charlie, 11),
+                Err(Error::TransferExceedsMax)
+            );
+            // The rejected transfer does not use up the allowance.
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 50);
+            assert_eq!(contract.balance_of(accounts.alice), 55);
+            assert_eq!(contract.balance_of(accounts.bob), 45);
+            assert_eq!(contract.balance_of(accounts.charlie), 0);
+
+            // Only the owner may change the limit.
+            assert_eq!(contract.set_max_transfer(None), Err(Error::NotOwner));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_max_transfer(None), Ok(()));
+            assert_eq!(contract.transfer(accounts.bob, 55), Ok(()));
         }
 
         #[ink::test]
-        fn transfer_from_insufficient() {
+        fn rewards_are_distributed_proportionally() {
+            let mut contract = Erc20::new(600);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            // The contract account holding the reward pool defaults to Alice.
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.transfer(accounts.bob, 100), Ok(()));
+            // Alice holds 300 and Bob 100 after the deposit: 0.5 per token.
+            assert_eq!(contract.deposit_rewards(200), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 300);
+            assert_eq!(contract.rewards_of(accounts.alice), Ok(150));
+            assert_eq!(contract.rewards_of(accounts.bob), Ok(50));
+
+            // Transfers checkpoint the rewards accrued with the previous balances.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.transfer(accounts.alice, 50), Ok(()));
+            assert_eq!(contract.rewards_of(accounts.alice), Ok(150));
+            assert_eq!(contract.rewards_of(accounts.bob), Ok(50));
+
+            // Alice holds 200 and Bob 50 after the deposit: 0.6 per token.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.deposit_rewards(150), Ok(()));
+            assert_eq!(contract.rewards_of(accounts.alice), Ok(270));
+            assert_eq!(contract.rewards_of(accounts.bob), Ok(80));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_rewards(), Ok(80));
+            assert_eq!(contract.balance_of(accounts.bob), 130);
+            assert_eq!(contract.rewards_of(accounts.bob), Ok(0));
+            assert_eq!(contract.claim_rewards(), Ok(0));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.claim_rewards(), Ok(270));
+            assert_eq!(contract.balance_of(accounts.alice), 470);
+            // The reward pool has been paid out completely.
+            assert_eq!(contract.balance_of(accounts.django), 0);
+            assert_eq!(contract.total_supply(), 600);
+        }
+
+        #[ink::test]
+        fn rewards_can_be_claimed_after_migration() {
+            let mut contract = Erc20::new(600);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.transfer(accounts.bob, 100), Ok(()));
+            assert_eq!(contract.deposit_rewards(200), Ok(()));
+
+            // The reward pool can not be overwritten by the migration.
+            assert_eq!(
+                contract.migrate_from_vec(vec![(accounts.django, 1)]),
+                Err(Error::DuplicateAccount)
+            );
+            assert_eq!(
+                contract
+                    .migrate_from_vec(vec![(accounts.alice, 10), (accounts.charlie, 20)]),
+                Ok(())
+            );
+            assert_eq!(contract.balance_of(accounts.django), 200);
+            assert_eq!(contract.total_supply(), 230);
+            assert_eq!(contract.rewards_of(accounts.alice), Ok(150));
+            assert_eq!(contract.rewards_of(accounts.bob), Ok(50));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_rewards(), Ok(50));
+            assert_eq!(contract.balance_of(accounts.bob), 50);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.claim_rewards(), Ok(150));
+            assert_eq!(contract.balance_of(accounts.alice), 160);
+            assert_eq!(contract.balance_of(accounts.django), 0);
+            assert_eq!(contract.total_supply(), 230);
+        }
+
+        #[ink::test]
+        fn rewards_of_large_balances_do_not_overflow() {
+            // Even so that the rewards per token are not rounded.
+            let large = Balance::MAX / REWARD_PRECISION / 2 * 2;
+            let mut contract = Erc20::new(large + large / 2);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.transfer(accounts.bob, large / 2), Ok(()));
+            // Bob is the only eligible holder for both deposits, so he accrues
+            // four rewards per token and `balance * reward_per_token` exceeds
+            // `Balance::MAX` while his rewards do not.
+            assert_eq!(contract.deposit_rewards(large), Ok(()));
+            assert_eq!(contract.batch_mint(vec![(accounts.alice, large)]), Ok(()));
+            assert_eq!(contract.deposit_rewards(large), Ok(()));
+            assert_eq!(contract.rewards_of(accounts.bob), Ok(2 * large));
+            assert_eq!(contract.rewards_of(accounts.alice), Ok(0));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_rewards(), Ok(2 * large));
+            assert_eq!(contract.balance_of(accounts.bob), 2 * large + large / 2);
+            assert_eq!(contract.balance_of(accounts.django), 0);
+        }
+
+        #[ink::test]
+        fn claim_rewards_fails_if_the_pool_is_short() {
+            let mut contract = Erc20::new(600);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.transfer(accounts.bob, 100), Ok(()));
+            assert_eq!(contract.deposit_rewards(200), Ok(()));
+            // Drain the reward pool behind the back of the reward accounting.
+            assert_eq!(contract.freeze(accounts.django), Ok(()));
+            assert_eq!(contract.clawback(accounts.django, accounts.eve, 190), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_rewards(), Err(Error::InsufficientRewardPool));
+            // The rewards are kept for a later claim.
+            assert_eq!(contract.rewards_of(accounts.bob), Ok(50));
+            assert_eq!(contract.balance_of(accounts.bob), 100);
+            assert_eq!(contract.balance_of(accounts.django), 10);
+        }
+
+        #[ink::test]
+        fn deposit_rewards_fails_without_recipients() {
             let mut contract = Erc20::new(100);
             let accounts =
                 ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.deposit_rewards(101), Err(Error::InsufficientBalance));
+            assert_eq!(contract.deposit_rewards(100), Err(Error::NoRewardRecipients));
+            assert_eq!(contract.total_supply(), 100);
+        }
 
-            // Check the balance of alice (default caller) and set 20 allowance to bob
-            assert_eq!(contract.balance_of(accounts.alice), 100);
-            contract.approve(accounts.bob, 20);
+        #[ink::test]
+        fn pausing_transfers_only_blocks_transfers() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, 50), Ok(()));
+            assert_eq!(contract.set_transfers_paused(true), Ok(()));
+            assert!(contract.transfers_paused());
+            assert_eq!(contract.transfer(accounts.bob, 1), Err(Error::Paused));
+            assert_eq!(
+                contract.batch_transfer(vec![(accounts.bob, 1)]),
+                Err(Error::Paused)
+            );
+            // Minting and burning are still possible.
+            assert_eq!(contract.batch_mint(vec![(accounts.charlie, 10)]), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.transfer_from(accounts.alice, accounts.bob, 1),
+                Err(Error::Paused)
+            );
+            // The allowance is left untouched by the paused transfer.
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 50);
+            assert_eq!(contract.burn_from(accounts.alice, 5), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 95);
 
-            // Set the contract as callee and Bob as caller.
-            let contract_addr = ink_env::account_id::<ink_env::DefaultEnvironment>();
-            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract_addr);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_transfers_paused(false), Ok(()));
+            assert_eq!(contract.transfer(accounts.bob, 1), Ok(()));
+        }
+
+        #[ink::test]
+        fn pausing_mint_only_blocks_minting() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.set_mint_paused(true), Ok(()));
+            assert!(contract.mint_paused());
+            assert_eq!(
+                contract.batch_mint(vec![(accounts.bob, 10)]),
+                Err(Error::Paused)
+            );
+            assert_eq!(contract.total_supply(), 100);
+            // Transfers and burning are still possible.
+            assert_eq!(contract.transfer(accounts.bob, 10), Ok(()));
+            assert_eq!(contract.approve(accounts.bob, 5), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.burn_from(accounts.alice, 5), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_mint_paused(false), Ok(()));
+            assert_eq!(contract.batch_mint(vec![(accounts.bob, 10)]), Ok(()));
+            assert_eq!(contract.total_supply(), 105);
+        }
+
+        #[ink::test]
+        fn pausing_burn_only_blocks_burning() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(contract.set_burn_paused(true), Ok(()));
+            assert!(contract.burn_paused());
+            // Transfers and minting are still possible.
+            assert_eq!(contract.transfer(accounts.charlie, 10), Ok(()));
+            assert_eq!(contract.batch_mint(vec![(accounts.charlie, 10)]), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.burn_from(accounts.alice, 5), Err(Error::Paused));
+            assert_eq!(contract.balance_of(accounts.alice), 90);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_burn_paused(false), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.burn_from(accounts.alice, 5), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 85);
+        }
+
+        #[ink::test]
+        fn pause_flags_are_owner_only() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
             ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_transfers_paused(true), Err(Error::NotOwner));
+            assert_eq!(contract.set_mint_paused(true), Err(Error::NotOwner));
+            assert_eq!(contract.set_burn_paused(true), Err(Error::NotOwner));
+            assert!(!contract.transfers_paused());
+            assert!(!contract.mint_paused());
+            assert!(!contract.burn_paused());
+        }
+
+        #[ink::test]
+        fn freezing_blocks_sending_only() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.transfer(accounts.bob, 30), Ok(()));
+            assert_eq!(contract.approve(accounts.charlie, 10), Ok(()));
+            assert_eq!(contract.freeze(accounts.alice), Ok(()));
+            assert!(contract.is_frozen(accounts.alice));
 
+            assert_eq!(contract.transfer(accounts.bob, 1), Err(Error::AccountFrozen));
             assert_eq!(
-                contract.transfer_from(accounts.alice, accounts.charlie, 30),
-                Err(Error::InsufficientAllowance)
+                contract.batch_transfer(vec![(accounts.bob, 1)]),
+                Err(Error::AccountFrozen)
+            );
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                contract.transfer_from(accounts.alice, accounts.charlie, 5),
+                Err(Error::AccountFrozen)
+            );
+            assert_eq!(contract.allowance(accounts.alice, accounts.charlie), 10);
+            // Frozen accounts can still receive tokens.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.transfer(accounts.alice, 5), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 75);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.unfreeze(accounts.alice), Ok(()));
+            assert_eq!(contract.unfreeze(accounts.alice), Err(Error::NotFrozen));
+            assert_eq!(contract.transfer(accounts.bob, 1), Ok(()));
+        }
+
+        #[ink::test]
+        fn clawback_works() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.transfer(accounts.bob, 30), Ok(()));
+            assert_eq!(contract.freeze(accounts.bob), Ok(()));
+            let events_before = ink_env::test::emitted_event_count();
+
+            // No allowance is required, but the balance must suffice.
+            assert_eq!(
+                contract.clawback(accounts.bob, accounts.charlie, 31),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(contract.clawback(accounts.bob, accounts.charlie, 20), Ok(()));
+            assert_eq!(contract.balance_of(accounts.bob), 10);
+            assert_eq!(contract.balance_of(accounts.charlie), 20);
+            assert_eq!(contract.total_supply(), 100);
+
+            assert_eq!(ink_env::test::emitted_event_count(), events_before + 1);
+            let event = ink_env::test::recorded_events()
+                .last()
+                .expect("a `Transfer` event must have been emitted");
+            match <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer")
+            {
+                Event::Transfer(Transfer { from, to, value }) => {
+                    assert_eq!(from, Some(accounts.bob));
+                    assert_eq!(to, Some(accounts.charlie));
+                    assert_eq!(value, 20);
+                }
+                _ => panic!("expected a `Transfer` event"),
+            }
+        }
+
+        #[ink::test]
+        fn clawback_to_the_frozen_account_itself_mints_nothing() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.transfer(accounts.bob, 30), Ok(()));
+            assert_eq!(contract.freeze(accounts.bob), Ok(()));
+            assert_eq!(contract.clawback(accounts.bob, accounts.bob, 20), Ok(()));
+            assert_eq!(contract.balance_of(accounts.bob), 30);
+            assert_eq!(contract.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn clawback_requires_frozen_account() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.transfer(accounts.bob, 30), Ok(()));
+            assert_eq!(
+                contract.clawback(accounts.bob, accounts.charlie, 10),
+                Err(Error::NotFrozen)
+            );
+            assert_eq!(contract.balance_of(accounts.bob), 30);
+            assert_eq!(contract.balance_of(accounts.charlie), 0);
+        }
+
+        #[ink::test]
+        fn freeze_and_clawback_are_owner_only() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.transfer(accounts.bob, 30), Ok(()));
+            assert_eq!(contract.freeze(accounts.bob), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                contract.clawback(accounts.bob, accounts.charlie, 10),
+                Err(Error::NotOwner)
+            );
+            assert_eq!(contract.freeze(accounts.alice), Err(Error::NotOwner));
+            assert_eq!(contract.unfreeze(accounts.bob), Err(Error::NotOwner));
+            assert_eq!(contract.balance_of(accounts.bob), 30);
+            assert!(contract.is_frozen(accounts.bob));
+            assert!(!contract.is_frozen(accounts.alice));
+        }
+
+        #[ink::test]
+        fn set_cap_rejects_cap_below_total_supply() {
+            let mut contract = Erc20::new(100);
+            assert_eq!(contract.cap(), None);
+            assert_eq!(contract.set_cap(Some(99)), Err(Error::CapExceeded));
+            assert_eq!(contract.set_cap(Some(100)), Ok(()));
+            assert_eq!(contract.cap(), Some(100));
+            assert_eq!(contract.set_cap(None), Ok(()));
+            assert_eq!(contract.cap(), None);
+        }
+
+        #[ink::test]
+        fn snapshot_is_owner_only() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.snapshot(), Err(Error::NotOwner));
+        }
+
+        /// Computes the topic of `entity` the way the off-chain engine does.
+        fn encoded_into_hash<T>(entity: &T) -> Hash
+        where
+            T: scale::Encode,
+        {
+            use ink_env::{
+                hash::{
+                    Blake2x256,
+                    CryptoHash,
+                    HashOutput,
+                },
+                Clear,
+            };
+            let mut result = Hash::clear();
+            let len_result = result.as_ref().len();
+            let encoded = entity.encode();
+            let len_encoded = encoded.len();
+            if len_encoded <= len_result {
+                result.as_mut()[..len_encoded].copy_from_slice(&encoded);
+                return result
+            }
+            let mut hash_output =
+                <<Blake2x256 as HashOutput>::Type as Default>::default();
+            <Blake2x256 as CryptoHash>::hash(&encoded, &mut hash_output);
+            let copy_len = core::cmp::min(hash_output.len(), len_result);
+            result.as_mut()[0..copy_len].copy_from_slice(&hash_output[0..copy_len]);
+            result
+        }
+
+        /// Asserts the `from` and `to` topics of the `Transfer` event at `index`.
+        fn assert_transfer_topics(
+            index: usize,
+            expected_from: Option<AccountId>,
+            expected_to: Option<AccountId>,
+        ) {
+            use ink_env::topics::PrefixedValue;
+            let event = ink_env::test::recorded_events()
+                .nth(index)
+                .expect("a `Transfer` event must have been emitted");
+            let expected_topics = [
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Erc20::Transfer",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    value: &expected_from,
+                    prefix: b"Erc20::Transfer::from",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    value: &expected_to,
+                    prefix: b"Erc20::Transfer::to",
+                }),
+            ];
+            let expected_topics = expected_topics
+                .iter()
+                .map(|topic| topic.as_ref().to_vec())
+                .collect::<Vec<_>>();
+            assert_eq!(event.topics, expected_topics);
+        }
+
+        #[ink::test]
+        fn transfer_topics_keep_absent_parties_by_default() {
+            let mut contract = Erc20::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            assert_eq!(contract.approve(accounts.bob, 10), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.burn_from(accounts.alice, 10), Ok(()));
+
+            assert_transfer_topics(0, None, Some(accounts.alice));
+            assert_transfer_topics(2, Some(accounts.alice), None);
+        }
+
+        #[ink::test]
+        fn transfer_topics_use_zero_address_if_configured() {
+            let mut contract = Erc20::new_with_zero_address_topics(100, true);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let zero = Some(AccountId::from([0x0; 32]));
+            assert_eq!(contract.approve(accounts.bob, 10), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.burn_from(accounts.alice, 10), Ok(()));
+
+            assert_transfer_topics(0, zero, Some(accounts.alice));
+            assert_transfer_topics(2, Some(accounts.alice), zero);
+            // Regular transfers are unaffected.
+            assert_eq!(contract.transfer(accounts.alice, 0), Ok(()));
+            assert_transfer_topics(3, Some(accounts.bob), Some(accounts.alice));
+        }
+    }
+
+    /// The off-chain engine behind `ink-experimental-engine` does not yet
+    /// support `block_timestamp`, so the time-dependent tests use the default one.
+    #[cfg(not(feature = "ink-experimental-engine"))]
+    #[cfg(test)]
+    mod rate_limit_tests {
+        use super::*;
+
+        use ink_env::{
+            call::Selector,
+            test::CallData,
+            DefaultEnvironment,
+        };
+        use ink_lang as ink;
+
+        /// Runs `f` as if it were called by `caller`.
+        fn call_as<R>(caller: AccountId, f: impl FnOnce() -> R) -> R {
+            let callee =
+                ink_env::test::get_current_contract_account_id::<DefaultEnvironment>()
+                    .expect("contract account id must exist");
+            ink_env::test::push_execution_context::<DefaultEnvironment>(
+                caller,
+                callee,
+                1_000_000,
+                0,
+                CallData::new(Selector::new([0x00; 4])),
+            );
+            let result = f();
+            ink_env::test::pop_execution_context();
+            result
+        }
+
+        #[ink::test]
+        fn rate_limit_works() {
+            let mut contract = Erc20::new(100);
+            let accounts = ink_env::test::default_accounts::<DefaultEnvironment>()
+                .expect("default accounts must exist");
+            // The default block time is 5, so the window spans two blocks.
+            assert_eq!(contract.set_rate_limit(10, 30), Ok(()));
+
+            // Transfers within the limit succeed.
+            assert_eq!(contract.transfer(accounts.bob, 20), Ok(()));
+            assert_eq!(contract.transfer(accounts.bob, 10), Ok(()));
+            // Exceeding the limit fails.
+            assert_eq!(contract.transfer(accounts.bob, 1), Err(Error::RateLimited));
+            assert_eq!(contract.balance_of(accounts.alice), 70);
+            // Other accounts have their own windows.
+            assert_eq!(
+                call_as(accounts.bob, || contract.transfer(accounts.charlie, 30)),
+                Ok(())
+            );
+
+            ink_env::test::advance_block::<DefaultEnvironment>()
+                .expect("cannot advance block");
+            assert_eq!(contract.transfer(accounts.bob, 1), Err(Error::RateLimited));
+
+            // The limit resets after the window.
+            ink_env::test::advance_block::<DefaultEnvironment>()
+                .expect("cannot advance block");
+            assert_eq!(contract.transfer(accounts.bob, 30), Ok(()));
+            assert_eq!(contract.transfer(accounts.bob, 1), Err(Error::RateLimited));
+
+            assert_eq!(contract.clear_rate_limit(), Ok(()));
+            assert_eq!(contract.transfer(accounts.bob, 40), Ok(()));
+            assert_eq!(contract.balance_of(accounts.alice), 0);
+        }
+
+        #[ink::test]
+        fn transfer_from_counts_against_the_owners_rate_limit() {
+            let mut contract = Erc20::new(100);
+            let accounts = ink_env::test::default_accounts::<DefaultEnvironment>()
+                .expect("default accounts must exist");
+            assert_eq!(contract.set_rate_limit(10, 30), Ok(()));
+            assert_eq!(contract.approve(accounts.bob, 50), Ok(()));
+            assert_eq!(contract.transfer(accounts.charlie, 20), Ok(()));
+
+            // Moving Alice's tokens via a spender uses up Alice's window.
+            assert_eq!(
+                call_as(accounts.bob, || {
+                    contract.transfer_from(accounts.alice, accounts.bob, 10)
+                }),
+                Ok(())
+            );
+            assert_eq!(
+                call_as(accounts.bob, || {
+                    contract.transfer_from(accounts.alice, accounts.bob, 1)
+                }),
+                Err(Error::RateLimited)
+            );
+            assert_eq!(contract.transfer(accounts.charlie, 1), Err(Error::RateLimited));
+            // The rejected transfer neither moves tokens nor spends the allowance.
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 40);
+            assert_eq!(contract.balance_of(accounts.alice), 70);
+            // The spender's own window is untouched.
+            assert_eq!(
+                call_as(accounts.bob, || contract.transfer(accounts.charlie, 10)),
+                Ok(())
+            );
+        }
+
+        #[ink::test]
+        fn rate_limit_is_owner_managed() {
+            let mut contract = Erc20::new(100);
+            let accounts = ink_env::test::default_accounts::<DefaultEnvironment>()
+                .expect("default accounts must exist");
+            assert_eq!(
+                call_as(accounts.bob, || contract.set_rate_limit(10, 30)),
+                Err(Error::NotOwner)
+            );
+            assert_eq!(
+                call_as(accounts.bob, || contract.clear_rate_limit()),
+                Err(Error::NotOwner)
+            );
+        }
+    }
+
+    /// Time-dependent allowance tests, see `rate_limit_tests` for why these use
+    /// the default off-chain engine.
+    #[cfg(not(feature = "ink-experimental-engine"))]
+    #[cfg(test)]
+    mod allowance_expiry_tests {
+        use super::*;
+
+        use ink_env::DefaultEnvironment;
+        use ink_lang as ink;
+
+        type Event = <Erc20 as ::ink_lang::reflect::ContractEventBase>::Type;
+
+        #[ink::test]
+        fn expired_allowances_can_not_be_spent() {
+            let mut contract = Erc20::new(100);
+            let accounts = ink_env::test::default_accounts::<DefaultEnvironment>()
+                .expect("default accounts must exist");
+            // The default block time is 5, so the allowance lasts for two blocks.
+            assert_eq!(contract.approve_until(accounts.bob, 10, 10), Ok(()));
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 10);
+            ink_env::test::advance_block::<DefaultEnvironment>()
+                .expect("cannot advance block");
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 10);
+            ink_env::test::advance_block::<DefaultEnvironment>()
+                .expect("cannot advance block");
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 0);
+            // A plain approval never expires.
+            assert_eq!(contract.approve(accounts.bob, 5), Ok(()));
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 5);
+        }
+
+        #[ink::test]
+        fn set_block_timestamp_controls_allowance_expiry() {
+            let mut contract = Erc20::new(100);
+            let accounts = ink_env::test::default_accounts::<DefaultEnvironment>()
+                .expect("default accounts must exist");
+            ink_env::test::set_block_timestamp::<DefaultEnvironment>(1_000)
+                .expect("cannot set block timestamp");
+            assert_eq!(ink_env::block_timestamp::<DefaultEnvironment>(), 1_000);
+            assert_eq!(contract.approve_until(accounts.bob, 10, 1_500), Ok(()));
+            // The timestamp survives message calls.
+            assert_eq!(ink_env::block_timestamp::<DefaultEnvironment>(), 1_000);
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 10);
+
+            ink_env::test::set_block_timestamp::<DefaultEnvironment>(1_499)
+                .expect("cannot set block timestamp");
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 10);
+            ink_env::test::set_block_timestamp::<DefaultEnvironment>(1_500)
+                .expect("cannot set block timestamp");
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 0);
+            assert_eq!(contract.prune_expired_allowances(10), 1);
+            assert_eq!(ink_env::block_timestamp::<DefaultEnvironment>(), 1_500);
+        }
+
+        #[ink::test]
+        fn reading_an_expired_allowance_leaves_it_stored() {
+            let mut contract = Erc20::new(100);
+            let accounts = ink_env::test::default_accounts::<DefaultEnvironment>()
+                .expect("default accounts must exist");
+            assert_eq!(contract.approve_until(accounts.bob, 10, 5), Ok(()));
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 10);
+            ink_env::test::advance_block::<DefaultEnvironment>()
+                .expect("cannot advance block");
+            // The effective allowance is zero once expired, however reading it
+            // neither removes the stale entry nor its expiry.
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 0);
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 0);
+            assert_eq!(
+                contract.allowances.get_pair(&accounts.alice, &accounts.bob),
+                Some(&10)
+            );
+            assert_eq!(
+                contract
+                    .allowance_expiries
+                    .get_pair(&accounts.alice, &accounts.bob),
+                Some(&5)
+            );
+            // Only pruning removes the stale entry.
+            assert_eq!(contract.prune_expired_allowances(10), 1);
+            assert_eq!(
+                contract.allowances.get_pair(&accounts.alice, &accounts.bob),
+                None
+            );
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn prune_expired_allowances_emits_events() {
+            let mut contract = Erc20::new(100);
+            let accounts = ink_env::test::default_accounts::<DefaultEnvironment>()
+                .expect("default accounts must exist");
+            assert_eq!(contract.approve_until(accounts.bob, 10, 5), Ok(()));
+            assert_eq!(contract.approve_until(accounts.charlie, 20, 5), Ok(()));
+            assert_eq!(contract.approve_until(accounts.django, 30, 100), Ok(()));
+            // Nothing has expired yet.
+            assert_eq!(contract.prune_expired_allowances(10), 0);
+
+            ink_env::test::advance_block::<DefaultEnvironment>()
+                .expect("cannot advance block");
+            let events_before = ink_env::test::recorded_events().count();
+            // The number of pruned allowances is bounded by `max`.
+            assert_eq!(contract.prune_expired_allowances(1), 1);
+            assert_eq!(contract.prune_expired_allowances(10), 1);
+            assert_eq!(contract.prune_expired_allowances(10), 0);
+            assert_eq!(contract.allowance(accounts.alice, accounts.django), 30);
+
+            let mut expired = ink_env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| {
+                    match <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer")
+                    {
+                        Event::AllowanceExpired(AllowanceExpired { owner, spender }) => {
+                            (owner, spender)
+                        }
+                        _ => panic!("expected an `AllowanceExpired` event"),
+                    }
+                })
+                .collect::<Vec<_>>();
+            expired.sort();
+            let mut expected =
+                vec![(accounts.alice, accounts.bob), (accounts.alice, accounts.charlie)];
+            expected.sort();
+            assert_eq!(expired, expected);
+        }
+
+        #[ink::test]
+        fn prune_expired_allowances_scans_at_most_max_expiries() {
+            let mut contract = Erc20::new(100);
+            let accounts = ink_env::test::default_accounts::<DefaultEnvironment>()
+                .expect("default accounts must exist");
+            // Allowances approved first but expiring later do not block pruning.
+            assert_eq!(contract.approve_until(accounts.django, 30, 100), Ok(()));
+            assert_eq!(contract.approve_until(accounts.bob, 10, 5), Ok(()));
+            assert_eq!(contract.approve_until(accounts.charlie, 20, 10), Ok(()));
+            // The expiry of bob is replaced, leaving a stale queued expiry.
+            assert_eq!(contract.approve(accounts.bob, 15), Ok(()));
+
+            ink_env::test::set_block_timestamp::<DefaultEnvironment>(10)
+                .expect("cannot set block timestamp");
+            // The stale expiry is scanned first and counts against `max`.
+            assert_eq!(contract.prune_expired_allowances(1), 0);
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 15);
+            assert_eq!(contract.prune_expired_allowances(1), 1);
+            assert_eq!(
+                contract.allowances.get_pair(&accounts.alice, &accounts.charlie),
+                None
             );
+            // Expiries in the future end the scan.
+            assert_eq!(contract.prune_expired_allowances(10), 0);
+            assert_eq!(contract.expiry_queue.len(), 1);
+            assert_eq!(contract.allowance(accounts.alice, accounts.django), 30);
         }
     }
 }